@@ -21,9 +21,14 @@ if ! test -w /dev/fb0; then
     exit 1
 fi
 
-if ! test -r /dev/input/mouse0; then
-    echo "FATAL: missing permision to read /dev/input/mouse0 (missing group input ???)"
-    ls -l //dev/input/mouse0
+# touch panels report through evdev (display 'evdev' config), legacy setup uses mouse0
+INPUT_OK=0
+for DEVICE in /dev/input/mouse0 /dev/input/event*; do
+    if test -r $DEVICE; then INPUT_OK=1; fi
+done
+if test $INPUT_OK -eq 0; then
+    echo "FATAL: missing permision to read /dev/input/mouse0 or /dev/input/event* (missing group input ???)"
+    ls -l /dev/input
     exit 1
 fi
 
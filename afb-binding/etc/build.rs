@@ -15,4 +15,7 @@ fn main() {
     println!("cargo:rustc-link-arg=-ljson-c");
     println!("cargo:rustc-link-arg=-llvgl");
     println!("cargo:rustc-link-arg=-llv_drivers");
+    // native fbdev/GTK init goes through capi-map.c, skipped by offscreen and drm drivers
+    println!("cargo:rustc-link-arg=-Wl,--wrap=fbdev_init");
+    println!("cargo:rustc-link-arg=-Wl,--wrap=gtkdrv_init");
 }
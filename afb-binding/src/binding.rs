@@ -15,17 +15,67 @@ use afbv4::prelude::*;
 use lvgl_gui::prelude::*;
 use serde::{Deserialize, Serialize};
 
-pub(crate) fn to_static_str(value: String) -> &'static str {
-    Box::leak(value.into_boxed_str())
+// empty filter matches every widget event
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub(crate) struct EventFilter {
+    #[serde(default)]
+    pub uids: Vec<String>,
+    pub pattern: Option<String>,
+    #[serde(default)]
+    pub events: Vec<String>,
 }
 
 AfbDataConverter!(api_arg_subscribe, QuerySubscribe);
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Serialize, Deserialize, Debug)]
 #[serde(tag = "action")]
 pub(crate) enum QuerySubscribe {
-    #[default]
-    SUBSCRIBE,
-    UNSUBSCRIBE,
+    SUBSCRIBE(EventFilter),
+    UNSUBSCRIBE(EventFilter),
+}
+
+impl Default for QuerySubscribe {
+    fn default() -> Self {
+        QuerySubscribe::SUBSCRIBE(EventFilter::default())
+    }
+}
+
+AfbDataConverter!(api_arg_screen, QueryScreen);
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct QueryScreen {
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub transition: ScreenTransition,
+    #[serde(default)]
+    pub time: u32,
+}
+
+AfbDataConverter!(api_arg_snapshot, QuerySnapshot);
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct QuerySnapshot {
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub save: bool,
+}
+
+AfbDataConverter!(api_arg_delete, QueryDelete);
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct QueryDelete {
+    pub uid: String,
+}
+
+AfbDataConverter!(api_arg_theme, QueryTheme);
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub(crate) struct QueryTheme {
+    pub uid: Option<String>,
+}
+
+AfbDataConverter!(api_arg_image, QueryImage);
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct QueryImage {
+    pub uid: String,
+    pub path: Option<String>,
+    pub data: Option<String>,
 }
 
 AfbDataConverter!(api_arg_switch, QueryOnOff);
@@ -37,20 +87,29 @@ pub(crate) enum QueryOnOff {
     OFF,
 }
 
-fn json_to_color(jcolor: JsoncObj) -> Result<LvglColor, AfbError> {
-    let red = jcolor.get::<u32>("red")?;
-    let blue = jcolor.get::<u32>("blue")?;
-    let green = jcolor.get::<u32>("green")?;
-
-    Ok(LvglColor::rvb(red as u8, green as u8, blue as u8))
-}
-
 // Binding init callback started at binding load time before any API exist
 // -----------------------------------------
 pub fn binding_init(rootv4: AfbApiV4, jconf: JsoncObj) -> Result<&'static AfbApi, AfbError> {
     // add binding custom converter
     api_arg_subscribe::register()?;
     api_arg_switch::register()?;
+    api_arg_screen::register()?;
+    api_arg_snapshot::register()?;
+    api_arg_delete::register()?;
+    api_arg_theme::register()?;
+    api_arg_image::register()?;
+
+    // check config against schema before anything is created, missing optional keys get their default
+    let config = match binding_config_from_json(jconf.to_string().as_str()) {
+        Ok(config) => config,
+        Err(errors) => {
+            for error in &errors {
+                afb_log_msg!(Error, rootv4, "{}", error);
+            }
+            return afb_error!("binding-config-fail", "{}", config_errors_to_string(&errors));
+        }
+    };
+    let jconf = JsoncObj::parse(config.to_string().as_str())?;
 
     let uid = if let Ok(value) = jconf.get::<String>("uid") {
         to_static_str(value)
@@ -90,8 +149,51 @@ pub fn binding_init(rootv4: AfbApiV4, jconf: JsoncObj) -> Result<&'static AfbApi
             let x_res = jvalue.get::<u32>("x_res")?;
             let y_res = jvalue.get::<u32>("y_res")?;
             let ratio = jvalue.get::<u32>("ratio")?;
+            let driver = match jvalue.get::<String>("driver") {
+                Ok(value) => match value.parse::<DisplayDriver>() {
+                    Ok(driver) => driver,
+                    Err(error) => return afb_error!("display-config-fail", "{}", error),
+                },
+                Err(_error) => DisplayDriver::default(),
+            };
+
+            // drm connector/mode come from optional 'drm' object, default first connected connector
+            let status = match (driver, jvalue.get::<JsoncObj>("drm")) {
+                (DisplayDriver::Drm, Ok(jdrm)) => drm_from_json(jdrm.to_string().as_str())
+                    .and_then(|config| {
+                        DisplayHandle::create_with_drm(x_res as i16, y_res as i16, ratio, &config)
+                    }),
+                _ => DisplayHandle::create_with_driver(x_res as i16, y_res as i16, ratio, driver),
+            };
+            let display = match status {
+                Ok(display) => display,
+                Err(error) => return afb_error!("display-config-fail", "{}", error),
+            };
+            if let Some(mode) = display.get_drm_mode() {
+                afb_log_msg!(
+                    Info,
+                    rootv4,
+                    "drm connector:{} mode:{}x{}@{}",
+                    mode.connector,
+                    mode.width,
+                    mode.height,
+                    mode.refresh
+                );
+            }
 
-            DisplayHandle::create(x_res as i16, y_res as i16, ratio)
+            // evdev touchscreen/keyboard, touch calibration can be changed at runtime with 'calibrate' verb
+            if let Ok(jevdev) = jvalue.get::<JsoncObj>("evdev") {
+                let configs = match evdev_from_json(jevdev.to_string().as_str()) {
+                    Ok(configs) => configs,
+                    Err(error) => return afb_error!("display-evdev-fail", "{}", error),
+                };
+                for config in &configs {
+                    if let Err(error) = display.add_evdev(config) {
+                        return afb_error!("display-evdev-fail", "{}", error);
+                    }
+                }
+            }
+            display
         }
         Err(_error) => {
             return afb_error!(
@@ -102,28 +204,63 @@ pub fn binding_init(rootv4: AfbApiV4, jconf: JsoncObj) -> Result<&'static AfbApi
     };
 
     if let Ok(value) = jconf.get::<String>("logo") {
-        LvglImage::new(display.get_root(), "tux-evse", value.as_str(), 0, 0);
+        display.draw_logo("logo", value.as_str());
+    }
+
+    // built-in day/night/high-contrast themes, config 'themes' may add or overload them
+    let (themes, theme) = match themes_from_config(&config) {
+        Ok(themes) => themes,
+        Err(error) => return afb_error!("display-theme-fail", "{}", error),
+    };
+    if let Some(config) = themes.iter().find(|config| config.uid == theme) {
+        if let Err(error) = display.set_theme_config(config) {
+            return afb_error!("display-theme-fail", "{}", error);
+        }
+    }
+
+    // session journal, replay with: lvgl-gui --replay journal.jsonl
+    if let Ok(path) = jconf.get::<String>("journal") {
+        match DisplayJournal::create(path.as_str(), &config) {
+            Ok(journal) => {
+                display.set_journal(journal);
+            }
+            Err(error) => return afb_error!("display-journal-fail", "{}", error),
+        }
     }
 
-    // check theme and provide default if needed
-    if let Ok(jvalue) = jconf.get::<JsoncObj>("theme") {
-        let dark = jvalue.get::<bool>("dark")?;
-        let primary = json_to_color(jvalue.get::<JsoncObj>("primary")?)?;
-        let secondary = json_to_color(jvalue.get::<JsoncObj>("secondary")?)?;
-        display.set_theme(primary, secondary, dark, LvglMkFont::std_14());
+    // optional screens or panel description, when missing the built-in demo panel is used
+    let panel = if let Ok(jscreens) = jconf.get::<JsoncObj>("screens") {
+        match screens_from_json(jscreens.to_string().as_str()) {
+            Ok(screens) => PanelConfig::Screens(screens),
+            Err(error) => return afb_error!("display-screens-fail", "{}", error),
+        }
+    } else if let Ok(jpanel) = jconf.get::<JsoncObj>("panel") {
+        match panel_from_json(jpanel.to_string().as_str()) {
+            Ok(panel) => PanelConfig::Panel(panel),
+            Err(error) => return afb_error!("display-panel-fail", "{}", error),
+        }
     } else {
-        let primary = LvglColor::LIGHT_BLUE();
-        let secondary = LvglColor::BLUE_GREY();
-        // Fulup TBD apply a correct theme
-        display.set_theme(primary, secondary, false, LvglMkFont::std_14());
+        PanelConfig::Demo
+    };
+
+    // snapshot saved on request are written within this directory
+    let snapshot_dir = if let Ok(value) = jconf.get::<String>("snapshot_dir") {
+        to_static_str(value)
+    } else {
+        "/tmp"
+    };
+
+    // runtime images loaded through 'image' verb are evicted (LRU) above this budget
+    if let Ok(value) = jconf.get::<u32>("image_cache_kb") {
+        display.set_image_budget(value as usize * 1024);
     }
 
+    // lock display in ram to avoid lvgl to free memory, verbs keep a static reference on it
+    let display = Box::leak(Box::new(display));
+
     // create backend API
     let api = AfbApi::new(api).set_info(info).set_permission(permission);
-    register_verbs(api , &mut display)?;
-
-    // lock config in ram to avoid lvgl to free memory
-    //Box::leak(Box::new(display));
+    register_verbs(api, permission, display, panel, snapshot_dir, themes, to_static_str(theme))?;
 
     Ok(api.finalize()?)
 }
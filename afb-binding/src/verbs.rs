@@ -12,53 +12,156 @@
 
 use crate::prelude::*;
 use afbv4::prelude::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use lvgl_gui::prelude::*;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-macro_rules! verb_by_uid {
-    ($api: ident, $display:ident, $uid:literal, $widget:ty, $ctx_type: ident) => {
-        let widget = match $display.get_by_uid($uid).downcast_ref::<$widget>() {
-            Some(widget) => widget,
+macro_rules! verb_by_widget {
+    ($display: ident, $widget:ident, $ctx_type: ident) => {
+        AfbVerb::new($widget.get_uid())
+            .set_info($widget.get_info())
+            .set_action($widget.get_action())?
+            .set_callback(Box::new($ctx_type {
+                display: $display,
+                uid: $widget.get_uid(),
+            }))
+    };
+}
+
+// widgets may be deleted/created at runtime, verbs resolve their widget from uid at each call
+macro_rules! widget_by_uid {
+    ($display: expr, $uid: expr, $variant: ident) => {
+        match $display.find_widget($uid) {
+            Some(entry) => match entry {
+                LvglWidget::$variant(this) => (entry, *this),
+                _ => return afb_error!("widget-verb-fail", "widget uid:{} type changed", $uid),
+            },
             None => {
-                return afb_error!(
-                    "verb-info-widget",
-                    "no widget uid:{} type:{} found in panel",
-                    $uid,
-                    stringify!($widget)
-                    )
+                let error = DisplayError::UnknownUid($uid.to_string());
+                return afb_error!("widget-verb-fail", "{}", error);
+            }
+        }
+    };
+    // shared verbs check widget input kind (cf: get_widget_input), jobs then match on widget type
+    ($display: expr, $uid: expr, input = $input: literal) => {
+        match $display.find_widget($uid) {
+            Some(entry) if get_widget_input(entry) == $input => entry,
+            Some(_) => return afb_error!("widget-verb-fail", "widget uid:{} type changed", $uid),
+            None => {
+                let error = DisplayError::UnknownUid($uid.to_string());
+                return afb_error!("widget-verb-fail", "{}", error);
+            }
+        }
+    };
+}
+
+// minimal glob matching, '*' any sequence and '?' any single char
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut pidx, mut tidx) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+
+    while tidx < text.len() {
+        if pidx < pattern.len() && (pattern[pidx] == '?' || pattern[pidx] == text[tidx]) {
+            pidx += 1;
+            tidx += 1;
+        } else if pidx < pattern.len() && pattern[pidx] == '*' {
+            star = Some((pidx, tidx));
+            pidx += 1;
+        } else if let Some((star_pidx, star_tidx)) = star {
+            pidx = star_pidx + 1;
+            tidx = star_tidx + 1;
+            star = Some((star_pidx, star_tidx + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[pidx..].iter().all(|char| *char == '*')
+}
+
+impl EventFilter {
+    pub fn is_empty(&self) -> bool {
+        self.uids.is_empty() && self.pattern.is_none() && self.events.is_empty()
+    }
+
+    pub fn matches(&self, uid: &str, event: &str) -> bool {
+        let uid_match = match &self.pattern {
+            None => self.uids.is_empty() || self.uids.iter().any(|value| value == uid),
+            Some(pattern) => {
+                self.uids.iter().any(|value| value == uid) || glob_match(pattern, uid)
             }
         };
+        let event_match = self.events.is_empty()
+            || self
+                .events
+                .iter()
+                .any(|value| value.eq_ignore_ascii_case(event));
+
+        uid_match && event_match
+    }
+}
 
-        let verb = AfbVerb::new(widget.get_uid())
-            .set_info(widget.get_info())
-            .set_action(widget.get_action())?
-            .set_callback(Box::new($ctx_type { widget }));
+// bounds afb events allocated for filters, events cannot be released once registered
+const EVENT_FILTERS_MAX: usize = 32;
 
-        $api.add_verb(verb)
-    };
+// each distinct filter owns a dedicated afb event, shared by every client using it. An event
+// without subscriber is idle and reused by the next new filter.
+struct FilterEvent {
+    filter: EventFilter,
+    event: &'static AfbEvent,
+    subscribers: usize,
+}
+
+pub(crate) struct EventFilters {
+    filters: Mutex<Vec<FilterEvent>>,
+}
+
+impl EventFilters {
+    fn new() -> &'static Self {
+        Box::leak(Box::new(EventFilters {
+            filters: Mutex::new(Vec::new()),
+        }))
+    }
+
+    fn push(&self, uid: &str, event: &str, jevent: &JsoncObj) {
+        let filters = match self.filters.lock() {
+            Ok(filters) => filters,
+            Err(_) => return,
+        };
+        for entry in filters.iter() {
+            if entry.subscribers > 0 && entry.filter.matches(uid, event) {
+                entry.event.push(jevent.clone());
+            }
+        }
+    }
 }
 
 struct WidgetEvtCtx {
     event: &'static AfbEvent,
+    filters: &'static EventFilters,
 }
 
 impl LvglHandler for WidgetEvtCtx {
     fn callback(&self, widget: &LvglWidget, uid: &'static str, event: &LvglEvent) {
-        match widget {
-            LvglWidget::Label(this) => {
-                println!("button:{} get event:{:?}", uid, event);
-                this.set_value("was pressed");
+        let payload = WidgetEvent::new(widget, uid, event);
+        match JsoncObj::parse(payload.to_json().as_str()) {
+            Ok(jevent) => {
+                self.filters.push(uid, payload.event, &jevent);
+                self.event.push(jevent);
+            }
+            Err(error) => {
+                afb_log_msg!(Error, None, "widget:{} event push fail error:{}", uid, error);
             }
-            _ => {}
         }
-
-        let info = format!("{{'uid':{}, 'event':{:?}}}", uid, event);
-        println!("*** {} ***", info);
-        self.event.push(info);
     }
 }
 
 struct SubscribeEvtCtx {
+    display: &'static DisplayHandle,
     event: &'static AfbEvent,
+    filters: &'static EventFilters,
 }
 
 AfbVerbRegister!(SubscribeEvtVerb, subscribe_evt_cb, SubscribeEvtCtx);
@@ -67,144 +170,857 @@ fn subscribe_evt_cb(
     args: &AfbData,
     ctx: &mut SubscribeEvtCtx,
 ) -> Result<(), AfbError> {
-    match args.get::<&QuerySubscribe>(0)? {
-        QuerySubscribe::SUBSCRIBE => {
+    let mut filters = match ctx.filters.filters.lock() {
+        Ok(filters) => filters,
+        Err(_) => return afb_error!("subscribe-filter-fail", "event filter lock poisoned"),
+    };
+
+    let query = args.get::<&QuerySubscribe>(0)?;
+    ctx.display.journal_verb("event", query);
+    match query {
+        // no filter: use global widget event
+        QuerySubscribe::SUBSCRIBE(filter) if filter.is_empty() => {
             ctx.event.subscribe(rqt)?;
+            let jreply = JsoncObj::new();
+            jreply.add("event", ctx.event.get_uid())?;
+            rqt.reply(jreply, 0);
         }
-        QuerySubscribe::UNSUBSCRIBE => {
+        QuerySubscribe::UNSUBSCRIBE(filter) if filter.is_empty() => {
             ctx.event.unsubscribe(rqt)?;
+            rqt.reply(AFB_NO_DATA, 0);
+        }
+        QuerySubscribe::SUBSCRIBE(filter) => {
+            let active = filters
+                .iter()
+                .position(|entry| entry.subscribers > 0 && entry.filter == *filter);
+            let idle = filters.iter().position(|entry| entry.subscribers == 0);
+            let index = match (active, idle) {
+                (Some(index), _) => index,
+                (None, Some(index)) => {
+                    filters[index].filter = filter.clone();
+                    index
+                }
+                (None, None) if filters.len() < EVENT_FILTERS_MAX => {
+                    let uid = to_static_str(format!("widget-{}", filters.len() + 1));
+                    let event = AfbEvent::new(uid);
+                    event.register(rqt.get_apiv4())?;
+                    filters.push(FilterEvent {
+                        filter: filter.clone(),
+                        event,
+                        subscribers: 0,
+                    });
+                    filters.len() - 1
+                }
+                (None, None) => {
+                    return afb_error!(
+                        "subscribe-filter-fail",
+                        "too many event filters (max:{})",
+                        EVENT_FILTERS_MAX
+                    )
+                }
+            };
+            let entry = &mut filters[index];
+            entry.event.subscribe(rqt)?;
+            entry.subscribers += 1;
+            let jreply = JsoncObj::new();
+            jreply.add("event", entry.event.get_uid())?;
+            rqt.reply(jreply, 0);
+        }
+        QuerySubscribe::UNSUBSCRIBE(filter) => {
+            match filters
+                .iter_mut()
+                .find(|entry| entry.subscribers > 0 && entry.filter == *filter)
+            {
+                Some(entry) => {
+                    entry.event.unsubscribe(rqt)?;
+                    entry.subscribers -= 1;
+                }
+                None => {
+                    return afb_error!(
+                        "unsubscribe-filter-fail",
+                        "no subscription matching filter:{:?}",
+                        filter
+                    )
+                }
+            }
+            rqt.reply(AFB_NO_DATA, 0);
         }
     }
-    rqt.reply(AFB_NO_DATA, 0);
     Ok(())
 }
 
-AfbVerbRegister!(InfoVerb, info_verb_cb, TextCtx);
+// journal records verb arguments as plain json
+fn jsonc_to_value(jquery: &JsoncObj) -> serde_json::Value {
+    serde_json::from_str(jquery.to_string().as_str()).unwrap_or_default()
+}
+
+// widget access is queued to lvgl thread, reply only once change is applied. Verb jobs only capture
+// owned arguments and 'static display/widget references (cf: DisplayHandle::exec safety).
+fn display_exec<T, F>(display: &DisplayHandle, uid: &str, job: F) -> Result<T, AfbError>
+where
+    F: FnOnce() -> T + 'static,
+    T: Send + 'static,
+{
+    match unsafe { display.exec(job) } {
+        Ok(value) => Ok(value),
+        Err(error) => afb_error!("display-exec-fail", "uid:{} {}", uid, error),
+    }
+}
+
+// widget may be deleted between verb lookup and job execution, check it is still indexed
+fn widget_exec<F>(
+    display: &'static DisplayHandle,
+    widget: &'static LvglWidget,
+    job: F,
+) -> Result<(), AfbError>
+where
+    F: FnOnce() + 'static,
+{
+    let uid = widget.get_uid();
+    let alive = display_exec(display, uid, move || {
+        let alive = matches!(display.find_widget(uid), Some(this) if std::ptr::eq(this, widget));
+        if alive {
+            job();
+        }
+        alive
+    })?;
+    if !alive {
+        return afb_error!("widget-verb-fail", "widget uid:{} deleted", uid);
+    }
+    Ok(())
+}
+
+// label, textarea, button and qrcode verbs take a text value
+AfbVerbRegister!(TextVerb, text_verb_cb, TextCtx);
 struct TextCtx {
-    widget: &'static LvglTextArea,
+    display: &'static DisplayHandle,
+    uid: &'static str,
 }
-fn info_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut TextCtx) -> Result<(), AfbError> {
+fn text_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut TextCtx) -> Result<(), AfbError> {
     let text = args.get::<String>(0)?;
-    ctx.widget.set_value(text.as_str());
+    ctx.display.journal_verb(ctx.uid, &text);
+    let entry = widget_by_uid!(ctx.display, ctx.uid, input = "text");
+    widget_exec(ctx.display, entry, move || match entry {
+        LvglWidget::Label(this) => {
+            this.set_value(text.as_str());
+        }
+        LvglWidget::TextArea(this) => {
+            this.set_value(text.as_str());
+        }
+        LvglWidget::Button(this) => {
+            this.set_value(text.as_str());
+        }
+        LvglWidget::Qrcode(this) => {
+            this.set_value(text.as_str());
+        }
+        _ => {}
+    })?;
     rqt.reply(AFB_NO_DATA, 0);
     Ok(())
 }
 
-struct MeterCtx {
-    widget: &'static LvglMeter,
+// meter, arc and bar verbs take an i32 value
+AfbVerbRegister!(IntVerb, int_verb_cb, IntCtx);
+struct IntCtx {
+    display: &'static DisplayHandle,
+    uid: &'static str,
 }
-AfbVerbRegister!(MeterVerb, meter_verb_cb, MeterCtx);
-fn meter_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut MeterCtx) -> Result<(), AfbError> {
+fn int_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut IntCtx) -> Result<(), AfbError> {
     let value = args.get::<i32>(0)?;
-    ctx.widget.set_value(value);
+    ctx.display.journal_verb(ctx.uid, &value);
+    let entry = widget_by_uid!(ctx.display, ctx.uid, input = "i32");
+    widget_exec(ctx.display, entry, move || match entry {
+        LvglWidget::Meter(this) => {
+            this.set_value(value);
+        }
+        LvglWidget::Arc(this) => {
+            this.set_value(value);
+        }
+        LvglWidget::Bar(this) => {
+            this.set_value(value);
+        }
+        _ => {}
+    })?;
     rqt.reply(AFB_NO_DATA, 0);
     Ok(())
 }
 
-AfbVerbRegister!(ArcVerb, arc_verb_cb, ArcCtx);
-struct ArcCtx {
-    widget: &'static LvglArc,
+// pixmap and pixbutton take a state name from widget config 'states' or directly an icon/asset name
+AfbVerbRegister!(PixmapVerb, pixmap_verb_cb, PixmapCtx);
+struct PixmapCtx {
+    display: &'static DisplayHandle,
+    uid: &'static str,
 }
-fn arc_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut ArcCtx) -> Result<(), AfbError> {
-    let value = args.get::<i32>(0)?;
-    ctx.widget.set_value(value);
+fn pixmap_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut PixmapCtx) -> Result<(), AfbError> {
+    let value = args.get::<String>(0)?;
+    let (display, uid) = (ctx.display, ctx.uid);
+    display.journal_verb(uid, &value);
+    let status = display_exec(display, uid, move || display.set_pixmap(uid, value.as_str()))?;
+    if let Err(error) = status {
+        return afb_error!("pixmap-verb-fail", "{}", error);
+    }
     rqt.reply(AFB_NO_DATA, 0);
     Ok(())
 }
 
-AfbVerbRegister!(BarVerb, bar_verb_cb, BarCtx);
-struct BarCtx {
-    widget: &'static LvglBar,
+AfbVerbRegister!(SwitchVerb, switch_verb_cb, SwitchCtx);
+struct SwitchCtx {
+    display: &'static DisplayHandle,
+    uid: &'static str,
 }
-fn bar_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut BarCtx) -> Result<(), AfbError> {
-    let value = args.get::<i32>(0)?;
-    ctx.widget.set_value(value);
+fn switch_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut SwitchCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryOnOff>(0)?;
+    ctx.display.journal_verb(ctx.uid, query);
+    let value = match query {
+        QueryOnOff::ON => true,
+        QueryOnOff::OFF => false,
+    };
+    let (entry, widget) = widget_by_uid!(ctx.display, ctx.uid, Switch);
+    widget_exec(ctx.display, entry, move || {
+        widget.set_value(value);
+    })?;
+    rqt.reply(AFB_NO_DATA, 0);
+    Ok(())
+}
+
+AfbVerbRegister!(LedVerb, led_verb_cb, LedCtx);
+struct LedCtx {
+    display: &'static DisplayHandle,
+    uid: &'static str,
+}
+fn led_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut LedCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryOnOff>(0)?;
+    ctx.display.journal_verb(ctx.uid, query);
+    let value = match query {
+        QueryOnOff::ON => true,
+        QueryOnOff::OFF => false,
+    };
+    let (entry, widget) = widget_by_uid!(ctx.display, ctx.uid, Led);
+    widget_exec(ctx.display, entry, move || {
+        widget.set_on(value);
+    })?;
     rqt.reply(AFB_NO_DATA, 0);
     Ok(())
 }
 
-AfbVerbRegister!(NfcVerb, ncf_verb_cb, NfcCtx);
-struct NfcCtx {
-    widget: &'static LvglPixButton,
+AfbVerbRegister!(GetVerb, get_verb_cb, GetCtx);
+struct GetCtx {
+    display: &'static DisplayHandle,
 }
-fn ncf_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut NfcCtx) -> Result<(), AfbError> {
-    match args.get::<&QueryOnOff>(0)? {
-        QueryOnOff::ON => {
-            ctx.widget.set_value(AssetPixmap::nfc_on());
+// return widget state as displayed on screen, all widgets when no uid is given
+fn get_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut GetCtx) -> Result<(), AfbError> {
+    let uid = match args.get::<JsoncObj>(0) {
+        Ok(jquery) => {
+            ctx.display.journal_verb("get", &jsonc_to_value(&jquery));
+            jquery.get::<String>("uid").ok()
+        }
+        Err(_) => {
+            ctx.display.journal_verb("get", &());
+            None
         }
-        QueryOnOff::OFF => {
-            ctx.widget.set_value(AssetPixmap::ethernet_on());
+    };
+
+    let display = ctx.display;
+    let jreply = match uid {
+        Some(uid) => {
+            let query = uid.clone();
+            let state = display_exec(display, uid.as_str(), move || {
+                display.get_state(query.as_str()).map(|state| state.to_json())
+            })?;
+            match state {
+                Some(state) => JsoncObj::parse(state.as_str())?,
+                None => return afb_error!("get-verb-fail", "no widget uid:{} found in panel", uid),
+            }
+        }
+        None => {
+            let states = display_exec(display, "*", move || {
+                display
+                    .get_panel()
+                    .iter()
+                    .map(|widget| WidgetState::new(widget).to_json())
+                    .collect::<Vec<String>>()
+            })?;
+            let jreply = JsoncObj::array();
+            for state in states {
+                jreply.append(JsoncObj::parse(state.as_str())?)?;
+            }
+            jreply
         }
+    };
+    rqt.reply(jreply, 0);
+    Ok(())
+}
+
+AfbVerbRegister!(PanelVerb, panel_verb_cb, PanelCtx);
+struct PanelCtx {
+    display: &'static DisplayHandle,
+}
+// list panel widgets with their type, accepted actions and geometry
+fn panel_verb_cb(rqt: &AfbRequest, _args: &AfbData, ctx: &mut PanelCtx) -> Result<(), AfbError> {
+    let display = ctx.display;
+    display.journal_verb("panel", &());
+    let infos = display_exec(display, "*", move || {
+        display
+            .get_panel()
+            .iter()
+            .map(|widget| WidgetInfo::new(widget).to_json())
+            .collect::<Vec<String>>()
+    })?;
+    let jreply = JsoncObj::array();
+    for info in infos {
+        jreply.append(JsoncObj::parse(info.as_str())?)?;
     }
-    rqt.reply(AFB_NO_DATA, 0);
+    rqt.reply(jreply, 0);
     Ok(())
 }
 
-AfbVerbRegister!(SwitchVerb, switch_verb_cb, SwitchCtx);
-struct SwitchCtx {
-    widget: &'static LvglSwitch,
+AfbVerbRegister!(ScreenVerb, screen_verb_cb, ScreenCtx);
+struct ScreenCtx {
+    display: &'static DisplayHandle,
+    event: &'static AfbEvent,
 }
-fn switch_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut SwitchCtx) -> Result<(), AfbError> {
-    match args.get::<&QueryOnOff>(0)? {
-        QueryOnOff::ON => {
-            ctx.widget.set_value(true);
+// switch to requested screen, without uid only return current screen and screen list
+fn screen_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut ScreenCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryScreen>(0)?;
+    ctx.display.journal_verb("screen", query);
+    let jreply = JsoncObj::new();
+
+    if let Some(uid) = &query.uid {
+        let display = ctx.display;
+        let (screen, transition, time) = (uid.clone(), query.transition, query.time);
+        let status = display_exec(display, uid.as_str(), move || {
+            display.set_screen(screen.as_str(), transition, time)
+        })?;
+        let previous = match status {
+            Ok(previous) => previous,
+            Err(error) => return afb_error!("screen-verb-fail", "{}", error),
+        };
+        if previous != uid.as_str() {
+            let jevent = JsoncObj::new();
+            jevent.add("screen", uid.as_str())?;
+            jevent.add("previous", previous)?;
+            ctx.event.push(jevent);
         }
-        QueryOnOff::OFF => {
-            ctx.widget.set_value(false);
+        jreply.add("previous", previous)?;
+    }
+
+    let jscreens = JsoncObj::array();
+    for screen in ctx.display.get_screens() {
+        jscreens.append(screen.get_uid())?;
+    }
+    jreply.add("screen", ctx.display.get_current_screen())?;
+    jreply.add("screens", jscreens)?;
+    rqt.reply(jreply, 0);
+    Ok(())
+}
+
+AfbVerbRegister!(SnapshotVerb, snapshot_verb_cb, SnapshotCtx);
+struct SnapshotCtx {
+    display: &'static DisplayHandle,
+    directory: &'static str,
+}
+
+// snapshot file name from uid, anything but [A-Za-z0-9_-] is replaced to stay within snapshot directory
+fn snapshot_name(uid: &str) -> String {
+    let name: String = uid
+        .chars()
+        .map(|char| match char {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => char,
+            _ => '_',
+        })
+        .collect();
+    if name.is_empty() {
+        "screen".to_string()
+    } else {
+        name
+    }
+}
+
+// return png as base64 or save it within configured snapshot directory
+fn snapshot_verb_cb(
+    rqt: &AfbRequest,
+    args: &AfbData,
+    ctx: &mut SnapshotCtx,
+) -> Result<(), AfbError> {
+    let query = args.get::<&QuerySnapshot>(0)?;
+    ctx.display.journal_verb("snapshot", query);
+    let uid = query.uid.as_deref();
+
+    let display = ctx.display;
+    let target = query.uid.clone();
+    let snapshot = display_exec(display, uid.unwrap_or("*"), move || {
+        display.snapshot(target.as_deref())
+    })?;
+    let png = match snapshot.and_then(|frame| {
+        let png = frame.to_png()?;
+        Ok((frame.width, frame.height, png))
+    }) {
+        Ok(value) => value,
+        Err(error) => return afb_error!("snapshot-verb-fail", "{}", error),
+    };
+
+    let jreply = JsoncObj::new();
+    jreply.add("width", png.0)?;
+    jreply.add("height", png.1)?;
+    if query.save {
+        let stamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis(),
+            Err(_) => 0,
+        };
+        let path = format!(
+            "{}/{}-{}.png",
+            ctx.directory,
+            snapshot_name(uid.unwrap_or(ctx.display.get_current_screen())),
+            stamp
+        );
+        if let Err(error) = std::fs::write(path.as_str(), png.2) {
+            return afb_error!("snapshot-verb-fail", "fail to write {}: {}", path, error);
         }
+        jreply.add("path", path.as_str())?;
+    } else {
+        jreply.add("png", STANDARD.encode(png.2).as_str())?;
     }
-    rqt.reply(AFB_NO_DATA, 0);
+    rqt.reply(jreply, 0);
     Ok(())
 }
 
-AfbVerbRegister!(LedVerb, led_verb_cb, LedCtx);
-struct LedCtx {
-    widget: &'static LvglLed,
+AfbVerbRegister!(BatchVerb, batch_verb_cb, BatchCtx);
+struct BatchCtx {
+    display: &'static DisplayHandle,
 }
-fn led_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut LedCtx) -> Result<(), AfbError> {
-    match args.get::<&QueryOnOff>(0)? {
-        QueryOnOff::ON => {
-            ctx.widget.set_on(true);
+// validate every operation first, then apply all of them within one lvgl refresh cycle
+fn batch_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut BatchCtx) -> Result<(), AfbError> {
+    let jquery = args.get::<JsoncObj>(0)?;
+    ctx.display.journal_verb("batch", &jsonc_to_value(&jquery));
+    let operations = match batch_from_json(jquery.to_string().as_str()) {
+        Ok(operations) => operations,
+        Err(error) => return afb_error!("batch-verb-fail", "{}", error),
+    };
+
+    // validation runs on lvgl thread to stay consistent with runtime create/delete
+    let display = ctx.display;
+    let status = display_exec(display, "batch", move || batch_apply(display, &operations))?;
+    let (results, status) = match status {
+        Ok(results) => (results, 0),
+        Err(results) => (results, -1),
+    };
+    let jreply = JsoncObj::parse(batch_results_to_json(&results).as_str())?;
+    rqt.reply(jreply, status);
+    Ok(())
+}
+
+AfbVerbRegister!(ThemeVerb, theme_verb_cb, ThemeCtx);
+struct ThemeCtx {
+    display: &'static DisplayHandle,
+    themes: Vec<ThemeConfig>,
+    current: String,
+}
+// switch theme when uid is given, reply with current and available themes
+fn theme_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut ThemeCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryTheme>(0)?;
+    ctx.display.journal_verb("theme", query);
+
+    if let Some(uid) = &query.uid {
+        let theme = match ctx.themes.iter().find(|theme| &theme.uid == uid) {
+            Some(theme) => theme.clone(),
+            None => return afb_error!("theme-verb-fail", "unknown theme uid:{}", uid),
+        };
+        let display = ctx.display;
+        let status = display_exec(display, uid.as_str(), move || display.apply_theme(&theme))?;
+        if let Err(error) = status {
+            return afb_error!("theme-verb-fail", "{}", error);
+        }
+        ctx.current = uid.clone();
+    }
+
+    let jthemes = JsoncObj::array();
+    for theme in &ctx.themes {
+        jthemes.append(theme.uid.as_str())?;
+    }
+    let jreply = JsoncObj::new();
+    jreply.add("theme", ctx.current.as_str())?;
+    jreply.add("themes", jthemes)?;
+    rqt.reply(jreply, 0);
+    Ok(())
+}
+
+// one verb per widget, handler is selected from widget type
+fn widget_verb(
+    display: &'static DisplayHandle,
+    widget: &'static LvglWidget,
+) -> Result<Option<&'static mut AfbVerb>, AfbError> {
+    let verb = match *widget {
+        LvglWidget::Label(this) => verb_by_widget!(display, this, TextCtx),
+        LvglWidget::TextArea(this) => verb_by_widget!(display, this, TextCtx),
+        LvglWidget::Button(this) => verb_by_widget!(display, this, TextCtx),
+        LvglWidget::Qrcode(this) => verb_by_widget!(display, this, TextCtx),
+        LvglWidget::Meter(this) => verb_by_widget!(display, this, IntCtx),
+        LvglWidget::Bar(this) => verb_by_widget!(display, this, IntCtx),
+        LvglWidget::Arc(this) => verb_by_widget!(display, this, IntCtx),
+        LvglWidget::Led(this) => verb_by_widget!(display, this, LedCtx),
+        LvglWidget::Switch(this) => verb_by_widget!(display, this, SwitchCtx),
+        LvglWidget::PixButton(this) => verb_by_widget!(display, this, PixmapCtx),
+        LvglWidget::Pixmap(this) => verb_by_widget!(display, this, PixmapCtx),
+        _ => return Ok(None), // widget without runtime method (line, area, ...)
+    };
+    Ok(Some(verb))
+}
+
+// afb verbs cannot be removed, keep track of uids that already own a verb
+// uid and widget type of registered verbs, verb context is bound to widget type at first creation
+struct WidgetVerbs {
+    uids: Mutex<Vec<(&'static str, &'static str)>>,
+}
+
+impl WidgetVerbs {
+    fn new() -> &'static Self {
+        Box::leak(Box::new(WidgetVerbs {
+            uids: Mutex::new(Vec::new()),
+        }))
+    }
+
+    fn push(&self, uid: &'static str, kind: &'static str) {
+        if let Ok(mut uids) = self.uids.lock() {
+            uids.push((uid, kind));
         }
-        QueryOnOff::OFF => {
-            ctx.widget.set_on(false);
+    }
+
+    fn get_type(&self, uid: &str) -> Option<&'static str> {
+        match self.uids.lock() {
+            Ok(uids) => uids
+                .iter()
+                .find(|(value, _)| *value == uid)
+                .map(|(_, kind)| *kind),
+            Err(_) => None,
         }
     }
-    rqt.reply(AFB_NO_DATA, 0);
+}
+
+AfbVerbRegister!(CreateVerb, create_verb_cb, CreateCtx);
+struct CreateCtx {
+    display: &'static DisplayHandle,
+    verbs: &'static WidgetVerbs,
+    permission: &'static AfbPermission,
+}
+fn create_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut CreateCtx) -> Result<(), AfbError> {
+    let jquery = args.get::<JsoncObj>(0)?;
+    ctx.display.journal_verb("create", &jsonc_to_value(&jquery));
+    let config = match create_from_json(jquery.to_string().as_str()) {
+        Ok(config) => config,
+        Err(error) => return afb_error!("create-verb-fail", "{}", error),
+    };
+
+    // a re-created uid reuses its previous verb, reject any type change
+    let mut retyped = None;
+    config.widget.for_each(&mut |widget| {
+        if let Some(kind) = ctx.verbs.get_type(widget.get_uid()) {
+            if kind != widget.get_type() && retyped.is_none() {
+                retyped = Some((widget.get_uid().to_string(), kind, widget.get_type()));
+            }
+        }
+    });
+    if let Some((uid, kind, requested)) = retyped {
+        return afb_error!(
+            "create-verb-fail",
+            "uid:{} verb is bound to type:{} cannot re-create it as type:{}",
+            uid,
+            kind,
+            requested
+        );
+    }
+
+    let display = ctx.display;
+    let status = display_exec(display, config.widget.get_uid(), move || {
+        display.create_widget(&config.widget, config.screen.as_deref())
+    })?;
+    let widgets = match status {
+        Ok(widgets) => widgets,
+        Err(error) => return afb_error!("create-verb-fail", "{}", error),
+    };
+
+    // register a verb with display api permission for uid never created before
+    let jreply = JsoncObj::array();
+    for widget in widgets {
+        let uid = widget.get_uid();
+        if ctx.verbs.get_type(uid).is_none() {
+            if let Some(verb) = widget_verb(display, widget)? {
+                verb.set_permission(ctx.permission)
+                    .finalize()?
+                    .register(rqt.get_apiv4(), AFB_NO_AUTH)?;
+                ctx.verbs.push(uid, get_widget_type(widget));
+            }
+        }
+        jreply.append(uid)?;
+    }
+    rqt.reply(jreply, 0);
+    Ok(())
+}
+
+// afb-v4 cannot unregister a verb: deleted widget verb stays registered and replies with an unknown uid error
+// until the same uid is created again (cf: WidgetVerbs)
+AfbVerbRegister!(DeleteVerb, delete_verb_cb, DeleteCtx);
+struct DeleteCtx {
+    display: &'static DisplayHandle,
+}
+fn delete_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut DeleteCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryDelete>(0)?;
+    ctx.display.journal_verb("delete", query);
+    let display = ctx.display;
+    let uid = query.uid.clone();
+    let status = display_exec(display, query.uid.as_str(), move || {
+        display.delete_widget(uid.as_str()).map(|widget| widget.get_uid())
+    })?;
+    match status {
+        Ok(uid) => {
+            let jreply = JsoncObj::new();
+            jreply.add("deleted", uid)?;
+            rqt.reply(jreply, 0);
+        }
+        Err(error) => return afb_error!("delete-verb-fail", "{}", error),
+    }
+    Ok(())
+}
+
+AfbVerbRegister!(ImageVerb, image_verb_cb, ImageCtx);
+struct ImageCtx {
+    display: &'static DisplayHandle,
+}
+fn image_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut ImageCtx) -> Result<(), AfbError> {
+    let query = args.get::<&QueryImage>(0)?;
+    ctx.display.journal_verb("image", query);
+    let display = ctx.display;
+    let source = match (&query.path, &query.data) {
+        (Some(path), None) => ImageSource::File(path.clone()),
+        (None, Some(data)) => match STANDARD.decode(data.as_str()) {
+            Ok(blob) => ImageSource::Data(blob),
+            Err(error) => return afb_error!("image-verb-fail", "invalid base64 data: {}", error),
+        },
+        _ => return afb_error!("image-verb-fail", "expect either 'path' or 'data'"),
+    };
+
+    // decode outside of lvgl thread, cached images are not decoded again
+    let decoded = if display.is_image_cached(source.get_key().as_str()) {
+        None
+    } else {
+        match source.decode(display.get_image_budget()) {
+            Ok(image) => Some(image),
+            Err(error) => return afb_error!("image-verb-fail", "uid:{} {}", query.uid, error),
+        }
+    };
+
+    let uid = query.uid.clone();
+    let status = display_exec(display, query.uid.as_str(), move || {
+        display.load_image(uid.as_str(), &source, decoded)
+    })?;
+    match status {
+        Ok(info) => {
+            let jreply = JsoncObj::parse(serde_json::to_string(&info).unwrap_or_default().as_str())?;
+            rqt.reply(jreply, 0);
+        }
+        Err(error) => return afb_error!("image-verb-fail", "{}", error),
+    }
+    Ok(())
+}
+
+AfbVerbRegister!(InputVerb, input_verb_cb, InputCtx);
+struct InputCtx {
+    display: &'static DisplayHandle,
+}
+// inject pointer/key input through lvgl virtual devices, reply with pointer coordinates
+fn input_verb_cb(rqt: &AfbRequest, args: &AfbData, ctx: &mut InputCtx) -> Result<(), AfbError> {
+    let jquery = args.get::<JsoncObj>(0)?;
+    ctx.display.journal_verb("input", &jsonc_to_value(&jquery));
+    let request = match input_from_json(jquery.to_string().as_str()) {
+        Ok(request) => request,
+        Err(error) => return afb_error!("input-verb-fail", "{}", error),
+    };
+
+    match input_inject(ctx.display, &request) {
+        Ok(point) => {
+            let jreply = JsoncObj::new();
+            if let Some(point) = point {
+                jreply.add("x", point.x as i64)?;
+                jreply.add("y", point.y as i64)?;
+            }
+            rqt.reply(jreply, 0);
+        }
+        Err(error) => return afb_error!("input-verb-fail", "{}", error),
+    }
+    Ok(())
+}
+
+AfbVerbRegister!(CalibrateVerb, calibrate_verb_cb, CalibrateCtx);
+struct CalibrateCtx {
+    display: &'static DisplayHandle,
+}
+// without arguments list evdev devices, else update touch calibration and reply with it
+fn calibrate_verb_cb(
+    rqt: &AfbRequest,
+    args: &AfbData,
+    ctx: &mut CalibrateCtx,
+) -> Result<(), AfbError> {
+    let display = ctx.display;
+    let jquery = match args.get::<JsoncObj>(0) {
+        Ok(jquery) => jquery,
+        Err(_) => {
+            let evdevs = display_exec(display, "calibrate", move || display.get_evdevs())?;
+            let jreply =
+                JsoncObj::parse(serde_json::to_string(&evdevs).unwrap_or_default().as_str())?;
+            rqt.reply(jreply, 0);
+            return Ok(());
+        }
+    };
+    display.journal_verb("calibrate", &jsonc_to_value(&jquery));
+    let request = match calibrate_from_json(jquery.to_string().as_str()) {
+        Ok(request) => request,
+        Err(error) => return afb_error!("calibrate-verb-fail", "{}", error),
+    };
+
+    let status = display_exec(display, "calibrate", move || display.calibrate(&request))?;
+    match status {
+        Ok(info) => {
+            let jreply = JsoncObj::parse(serde_json::to_string(&info).unwrap_or_default().as_str())?;
+            rqt.reply(jreply, 0);
+        }
+        Err(error) => return afb_error!("calibrate-verb-fail", "{}", error),
+    }
     Ok(())
 }
 
 pub(crate) fn register_verbs(
     api: &mut AfbApi,
-    display: &mut DisplayHandle,
+    permission: &'static AfbPermission,
+    display: &'static mut DisplayHandle,
+    panel: PanelConfig,
+    snapshot_dir: &'static str,
+    themes: Vec<ThemeConfig>,
+    theme: &'static str,
 ) -> Result<(), AfbError> {
-    // global display API event
+    // global display API event, filtered subscriptions get their own event at runtime
     let event = AfbEvent::new("widget");
+    let filters = EventFilters::new();
 
-    // build panel register display callback
-    display
-        .set_callback(Box::new(WidgetEvtCtx { event }))
-        .draw_panel()
-        .finalize();
+    // build panel from config or fallback to demo panel, then register display callback
+    display.set_callback(Box::new(WidgetEvtCtx { event, filters }));
+    if let Err(error) = display.draw_config(&panel) {
+        return afb_error!("display-panel-fail", "{}", error);
+    }
+    if let Err(error) = display.finalize() {
+        return afb_error!("display-panel-fail", "{}", error);
+    }
+    let display: &'static DisplayHandle = display;
 
     let subscribe = AfbVerb::new("event")
-        .set_info("subscribe to widget event")
+        .set_info("subscribe to widget event (optional filter: uids, pattern, events)")
         .set_action("['SUBSCRIBE','UNSUBSCRIBE']")?
-        .set_callback(Box::new(SubscribeEvtCtx { event }))
+        .set_callback(Box::new(SubscribeEvtCtx {
+            display,
+            event,
+            filters,
+        }))
         .finalize()?;
     api.add_verb(subscribe);
 
-    //create and register widget verbs (Warning type mismatch is only detected at runtime)
-    verb_by_uid!(api, display, "Text-Area", LvglTextArea, TextCtx);
-    verb_by_uid!(api, display, "Meter", LvglMeter, MeterCtx);
-    verb_by_uid!(api, display, "Led-Green", LvglLed, LedCtx);
-    verb_by_uid!(api, display, "Led-Red", LvglLed, LedCtx);
-    verb_by_uid!(api, display, "Switch-1", LvglSwitch, SwitchCtx);
-    verb_by_uid!(api, display, "Switch-2", LvglSwitch, SwitchCtx);
-    verb_by_uid!(api, display, "Bar-1", LvglBar, BarCtx);
-    verb_by_uid!(api, display, "Bar-2", LvglBar, BarCtx);
-    verb_by_uid!(api, display, "Arc", LvglArc, ArcCtx);
-    verb_by_uid!(api, display, "Pixmap-Button", LvglPixButton, NfcCtx);
+    let get = AfbVerb::new("get")
+        .set_info("return widget value, visibility, disabled flag and geometry")
+        .set_usage("{'uid':'widget-uid'}")
+        .set_callback(Box::new(GetCtx { display }))
+        .finalize()?;
+    api.add_verb(get);
+
+    let panel = AfbVerb::new("panel")
+        .set_info("list panel widgets with type, info, actions and geometry")
+        .set_callback(Box::new(PanelCtx { display }))
+        .finalize()?;
+    api.add_verb(panel);
+
+    let screen_event = AfbEvent::new("screen");
+    let screen = AfbVerb::new("screen")
+        .set_info("switch displayed screen (optional transition: fade, slide-left, slide-right)")
+        .set_usage("{'uid':'screen-uid', 'transition':'fade', 'time':300}")
+        .set_callback(Box::new(ScreenCtx {
+            display,
+            event: screen_event,
+        }))
+        .finalize()?;
+    api.add_verb(screen);
+
+    let snapshot = AfbVerb::new("snapshot")
+        .set_info("capture screen or widget as png (base64 or saved within snapshot_dir)")
+        .set_usage("{'uid':'widget-uid', 'save':false}")
+        .set_callback(Box::new(SnapshotCtx {
+            display,
+            directory: snapshot_dir,
+        }))
+        .finalize()?;
+    api.add_verb(snapshot);
+
+    let batch = AfbVerb::new("batch")
+        .set_info("apply many widget updates at once (action: set, show, hide, enable, disable)")
+        .set_usage("[{'uid':'Bar-1', 'action':'set', 'value':50}, {'uid':'Led-Red', 'value':'ON'}]")
+        .set_callback(Box::new(BatchCtx { display }))
+        .finalize()?;
+    api.add_verb(batch);
+
+    let theme = AfbVerb::new("theme")
+        .set_info("switch panel theme at runtime (day, night, high-contrast or config themes)")
+        .set_usage("{'uid':'night'}")
+        .set_callback(Box::new(ThemeCtx {
+            display,
+            themes,
+            current: theme.to_string(),
+        }))
+        .finalize()?;
+    api.add_verb(theme);
+    api.add_event(screen_event);
+
+    // create one verb per panel widget
+    let verbs = WidgetVerbs::new();
+    for widget in display.get_panel() {
+        if let Some(verb) = widget_verb(display, widget)? {
+            api.add_verb(verb);
+            verbs.push(widget.get_uid(), get_widget_type(widget));
+        }
+    }
+
+    let create = AfbVerb::new("create")
+        .set_info("create widget at runtime from its json description (optional screen uid)")
+        .set_usage("{'screen':'main', 'widget':{'type':'label', 'uid':'Label-2', 'value':'hello'}}")
+        .set_callback(Box::new(CreateCtx {
+            display,
+            verbs,
+            permission,
+        }))
+        .finalize()?;
+    api.add_verb(create);
+
+    let delete = AfbVerb::new("delete")
+        .set_info("delete widget at runtime, its verb then returns an error")
+        .set_usage("{'uid':'Label-2'}")
+        .set_callback(Box::new(DeleteCtx { display }))
+        .finalize()?;
+    api.add_verb(delete);
+
+    let image = AfbVerb::new("image")
+        .set_info("display png/jpeg/bmp file or base64 blob on pixmap or pixbutton widget")
+        .set_usage("{'uid':'Pixmap-Button', 'path':'/var/lib/lvgl/logo.png'}")
+        .set_callback(Box::new(ImageCtx { display }))
+        .finalize()?;
+    api.add_verb(image);
+
+    let input = AfbVerb::new("input")
+        .set_info("inject pointer click, press, release, drag or key on widget uid or x,y coordinates")
+        .set_usage("{'action':'click', 'uid':'Button-A'}")
+        .set_callback(Box::new(InputCtx { display }))
+        .finalize()?;
+    api.add_verb(input);
+
+    let calibrate = AfbVerb::new("calibrate")
+        .set_info("list evdev inputs or update touch calibration (swap_xy, invert_x/y, matrix or 3 points)")
+        .set_usage("{'device':'/dev/input/event1', 'swap_xy':true, 'invert_y':false}")
+        .set_callback(Box::new(CalibrateCtx { display }))
+        .finalize()?;
+    api.add_verb(calibrate);
 
     // register verb+event
     api.add_event(event);
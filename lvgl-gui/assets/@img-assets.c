@@ -15,4 +15,8 @@
 #include "tux_evsex150.c"
 #include "reception_off.c"
 #include "translate.c"
+#include "button_left.c"
+#include "button_mid.c"
+#include "button_right.c"
+#include "mouse_cursor.c"
 //@END_IMG_imgS@
\ No newline at end of file
@@ -15,5 +15,13 @@ impl AssetPixmap {
     impl_static_imgbin! (tux_evsex150, img_tux_evsex150);
     impl_static_imgbin! (reception_off, img_reception_off);
     impl_static_imgbin! (translate, img_translate);
+    impl_static_imgbin! (wifi_off, img__wifi_off);
+    impl_static_imgbin! (calendar3, asset_calendar3);
+    impl_static_imgbin! (ethernet_off, asset_ethernet_off);
+    impl_static_imgbin! (clock, asset_clock);
+    impl_static_imgbin! (button_left, asset_button_left);
+    impl_static_imgbin! (button_mid, asset_button_mid);
+    impl_static_imgbin! (button_right, asset_button_right);
+    impl_static_imgbin! (mouse_cursor, img_mouse_cursor);
 //@END_IMG_imgS@
 }
\ No newline at end of file
@@ -0,0 +1,261 @@
+/*
+ * Copyright (C) 2015-2023 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Redpesk interface code/config use MIT License and can be freely copy/modified even within proprietary code
+ * License: $RP_BEGIN_LICENSE$ SPDX:MIT https://opensource.org/licenses/MIT $RP_END_LICENSE$
+ *
+*/
+
+// Build time asset pipeline: every assets/png/*.png is converted to an lvgl image descriptor
+// (same layout as LVGL online converter) and exposed as AssetPixmap::<name>().
+//  - LVGL_ASSETS_DIR overloads png source directory
+//  - LVGL_ASSET_CF selects color format: true_color_alpha (default) or true_color
+
+use std::env;
+use std::fmt::Write;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, PartialEq)]
+enum AssetFormat {
+    TrueColor,
+    TrueColorAlpha,
+}
+
+impl AssetFormat {
+    fn from_env() -> Self {
+        match env::var("LVGL_ASSET_CF") {
+            Ok(value) => match value.to_lowercase().as_str() {
+                "true_color" => AssetFormat::TrueColor,
+                "true_color_alpha" => AssetFormat::TrueColorAlpha,
+                _ => panic!("LVGL_ASSET_CF={} unsupported (true_color|true_color_alpha)", value),
+            },
+            Err(_) => AssetFormat::TrueColorAlpha,
+        }
+    }
+
+    fn to_cf(self) -> &'static str {
+        match self {
+            AssetFormat::TrueColor => "LV_IMG_CF_TRUE_COLOR",
+            AssetFormat::TrueColorAlpha => "LV_IMG_CF_TRUE_COLOR_ALPHA",
+        }
+    }
+
+    fn to_size(self) -> &'static str {
+        match self {
+            AssetFormat::TrueColor => "LV_COLOR_SIZE / 8",
+            AssetFormat::TrueColorAlpha => "LV_IMG_PX_SIZE_ALPHA_BYTE",
+        }
+    }
+}
+
+struct AssetImage {
+    name: String,
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+// decode png and normalize it to RGBA8888
+fn decode_png(path: &Path) -> AssetImage {
+    let file = File::open(path).unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder
+        .read_info()
+        .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    let mut data = vec![0u8; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut data)
+        .unwrap_or_else(|error| panic!("{}: {}", path.display(), error));
+    let data = &data[..info.buffer_size()];
+
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 0xFF])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks(2)
+            .flat_map(|pixel| [pixel[0], pixel[0], pixel[0], pixel[1]])
+            .collect(),
+        png::ColorType::Grayscale => data
+            .iter()
+            .flat_map(|gray| [*gray, *gray, *gray, 0xFF])
+            .collect(),
+        color => panic!("{}: unsupported color type {:?}", path.display(), color),
+    };
+
+    // asset name is png file stem, ex: tux-evsex250.png => tux_evsex250
+    let name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or_default()
+        .to_lowercase()
+        .replace(|char: char| !char.is_ascii_alphanumeric(), "_");
+
+    AssetImage {
+        name,
+        width: info.width,
+        height: info.height,
+        rgba,
+    }
+}
+
+// one pixel for each LV_COLOR_DEPTH, alpha byte follows color when requested
+fn pixel_rgb332(pixel: &[u8]) -> Vec<u8> {
+    vec![(pixel[0] & 0xE0) | ((pixel[1] & 0xE0) >> 3) | (pixel[2] >> 6)]
+}
+
+fn pixel_rgb565(pixel: &[u8], swap: bool) -> Vec<u8> {
+    let value = ((pixel[0] as u16 >> 3) << 11) | ((pixel[1] as u16 >> 2) << 5) | (pixel[2] as u16 >> 3);
+    if swap {
+        vec![(value >> 8) as u8, value as u8]
+    } else {
+        vec![value as u8, (value >> 8) as u8]
+    }
+}
+
+// lv_color32_t alpha byte is opaque, with true_color_alpha real alpha is appended by write_map instead
+fn pixel_bgra(pixel: &[u8], format: AssetFormat) -> Vec<u8> {
+    match format {
+        AssetFormat::TrueColor => vec![pixel[2], pixel[1], pixel[0], 0xFF],
+        AssetFormat::TrueColorAlpha => vec![pixel[2], pixel[1], pixel[0]],
+    }
+}
+
+fn write_map(
+    output: &mut String,
+    condition: &str,
+    image: &AssetImage,
+    format: AssetFormat,
+    to_color: &dyn Fn(&[u8]) -> Vec<u8>,
+) {
+    let _ = writeln!(output, "#if {}", condition);
+    for row in image.rgba.chunks(image.width as usize * 4) {
+        output.push(' ');
+        for pixel in row.chunks(4) {
+            let mut bytes = to_color(pixel);
+            if format == AssetFormat::TrueColorAlpha {
+                bytes.push(pixel[3]);
+            }
+            for byte in bytes {
+                let _ = write!(output, " 0x{:02x},", byte);
+            }
+        }
+        output.push('\n');
+    }
+    output.push_str("#endif\n");
+}
+
+fn write_image(output: &mut String, image: &AssetImage, format: AssetFormat) {
+    let _ = writeln!(output, "\n// {} {}x{}", image.name, image.width, image.height);
+    let _ = writeln!(
+        output,
+        "const LV_ATTRIBUTE_MEM_ALIGN LV_ATTRIBUTE_LARGE_CONST uint8_t gen_{}_map[] = {{",
+        image.name
+    );
+    write_map(output, "LV_COLOR_DEPTH == 1 || LV_COLOR_DEPTH == 8", image, format, &pixel_rgb332);
+    write_map(output, "LV_COLOR_DEPTH == 16 && LV_COLOR_16_SWAP == 0", image, format, &|pixel| {
+        pixel_rgb565(pixel, false)
+    });
+    write_map(output, "LV_COLOR_DEPTH == 16 && LV_COLOR_16_SWAP != 0", image, format, &|pixel| {
+        pixel_rgb565(pixel, true)
+    });
+    write_map(output, "LV_COLOR_DEPTH == 32", image, format, &|pixel| {
+        pixel_bgra(pixel, format)
+    });
+    output.push_str("};\n");
+
+    let _ = writeln!(
+        output,
+        "const lv_img_dsc_t img_gen_{name} = {{
+  .header.cf = {cf},
+  .header.always_zero = 0,
+  .header.reserved = 0,
+  .header.w = {width},
+  .header.h = {height},
+  .data_size = {count} * {size},
+  .data = gen_{name}_map,
+}};",
+        name = image.name,
+        cf = format.to_cf(),
+        width = image.width,
+        height = image.height,
+        count = image.width * image.height,
+        size = format.to_size(),
+    );
+}
+
+// names already exposed by hand written assets/@img-assets.rs
+fn static_names(assets: &Path) -> Vec<String> {
+    println!("cargo:rerun-if-changed={}", assets.join("@img-assets.rs").display());
+    let source = fs::read_to_string(assets.join("@img-assets.rs")).unwrap_or_default();
+    source
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("impl_static_imgbin! ("))
+        .filter_map(|line| line.split(',').next())
+        .map(|name| name.trim().to_string())
+        .collect()
+}
+
+// generate OUT_DIR/img-generated.c (included from capi-map.c) and OUT_DIR/img-generated.rs
+pub fn generate(manifest_dir: &Path, out_dir: &Path) {
+    let assets = manifest_dir.join("assets");
+    let source_dir = match env::var("LVGL_ASSETS_DIR") {
+        Ok(value) => PathBuf::from(value),
+        Err(_) => assets.join("png"),
+    };
+    println!("cargo:rerun-if-env-changed=LVGL_ASSETS_DIR");
+    println!("cargo:rerun-if-env-changed=LVGL_ASSET_CF");
+    println!("cargo:rerun-if-changed={}", source_dir.display());
+
+    let format = AssetFormat::from_env();
+    let mut sources: Vec<PathBuf> = match fs::read_dir(&source_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("png")))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    sources.sort();
+
+    let reserved = static_names(&assets);
+    let mut c_output = String::from(
+        "// generated by capi/build.rs from png assets, do not edit\n\
+         #ifndef LV_ATTRIBUTE_MEM_ALIGN\n#define LV_ATTRIBUTE_MEM_ALIGN\n#endif\n",
+    );
+    let mut rs_output = String::from(
+        "// generated by capi/build.rs from png assets, do not edit\n\
+         #[allow(dead_code)]\nimpl AssetPixmap {\n",
+    );
+
+    let mut names: Vec<String> = Vec::new();
+    for path in &sources {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let image = decode_png(path);
+        if reserved.contains(&image.name) || names.contains(&image.name) {
+            panic!("{}: asset name '{}' already exists", path.display(), image.name);
+        }
+        write_image(&mut c_output, &image, format);
+        let _ = writeln!(rs_output, "    impl_static_imgbin! ({name}, img_gen_{name});", name = image.name);
+        names.push(image.name);
+    }
+    rs_output.push_str("}\n");
+
+    // name registry for AssetPixmap::by_name/iter, hand written assets first
+    let _ = writeln!(
+        rs_output,
+        "\npub(crate) const ASSET_REGISTRY: [(&str, fn() -> &'static LvglImgDsc); {}] = [",
+        reserved.len() + names.len()
+    );
+    for name in reserved.iter().chain(names.iter()) {
+        let _ = writeln!(rs_output, "    (\"{name}\", AssetPixmap::{name}),", name = name);
+    }
+    rs_output.push_str("];\n");
+
+    fs::write(out_dir.join("img-generated.c"), c_output).expect("Couldn't write img-generated.c");
+    fs::write(out_dir.join("img-generated.rs"), rs_output).expect("Couldn't write img-generated.rs");
+}
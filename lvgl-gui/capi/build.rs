@@ -7,6 +7,10 @@
  *
 */
 use std::env;
+use std::path::PathBuf;
+
+#[path = "build-assets.rs"]
+mod assets;
 
 fn main() {
     // check pkgconfig dependencies
@@ -17,6 +21,9 @@ fn main() {
     println!("cargo:rustc-link-search=/usr/local/lib64");
     println!("cargo:rustc-link-arg=-llvgl");
     println!("cargo:rustc-link-arg=-llv_drivers");
+    // native fbdev/GTK init goes through capi-map.c, skipped by offscreen and drm drivers
+    println!("cargo:rustc-link-arg=-Wl,--wrap=fbdev_init");
+    println!("cargo:rustc-link-arg=-Wl,--wrap=gtkdrv_init");
     if let Ok(value) = env::var("CARGO_TARGET_DIR") {
         if let Ok(profile) = env::var("PROFILE") {
             println!("cargo:rustc-link-search=crate={}{}", value, profile);
@@ -49,14 +56,38 @@ fn main() {
         Err(_) => 0,
     };
 
+    // convert png assets into lvgl image descriptors
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    assets::generate(&manifest_dir, &out_dir);
+
+    if env::var("CARGO_FEATURE_OFFSCREEN").is_ok() {
+        println!("cargo:warning=offscreen display backend selected");
+    }
+
+    // DRM/KMS backend needs libdrm, it is only used when display driver is 'drm'
+    let drm_selected = match env::var("CARGO_FEATURE_DRM") {
+        Ok(_value) => {
+            println!("cargo:warning=DRM/KMS display backend available");
+            1
+        }
+        Err(_) => 0,
+    };
+
     let _capi_map = bindgen::Builder::default()
         .header("capi/capi-map.c")
         .clang_arg("-I/usr/local/include/lvgl")
+        .clang_arg(format!("-I{}", out_dir.display()))
+        .clang_arg(format!("-DUSE_DRM={}", drm_selected))
+        .clang_arg("-I/usr/include/libdrm")
+        .clang_arg("-I/usr/local/include/libdrm")
         .raw_line(header)
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
         .derive_debug(false)
         .layout_tests(false)
         .allowlist_item("img_.*")
+        .allowlist_item("asset_.*")
+        .allowlist_function("capi_.*")
         .blocklist_type("lv_img_dsc_t") // defined in lvgl-rclib
         .generate()
         .expect("Unable to generate _capi-map.rs");
@@ -69,7 +100,11 @@ fn main() {
     cc::Build::new()
         .file("capi/capi-map.c")
         .define("USE_GTK", defined.as_str())
+        .define("USE_DRM", drm_selected.to_string().as_str())
+        .include("/usr/include/libdrm")
+        .include("/usr/local/include/libdrm")
         .include("/usr/local/include/lvgl")
         .include("/usr/local/include")
+        .include(&out_dir)
         .compile("lvgl-asset");
 }
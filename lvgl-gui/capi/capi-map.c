@@ -17,9 +17,1028 @@
 
 // include your LVGL compiled asset
 #include "lvgl/lvgl.h"
+#include <stdlib.h>
+#include <string.h>
+#include <errno.h>
+#include <fcntl.h>
+#include <unistd.h>
+#include <sys/ioctl.h>
+#include <linux/input.h>
 #include "../assets/@img-assets.c"
+#include "img-generated.c" // build.rs png asset pipeline (OUT_DIR)
 
 // include here after any C function to import
 // dont forget to update build.rs allowed_item list
 
+// widget state read back, lvgl objects are exchanged as opaque pointers
+bool capi_obj_is_checked(void *obj) {
+    return lv_obj_has_state((lv_obj_t *)obj, LV_STATE_CHECKED);
+}
 
+bool capi_obj_is_hidden(void *obj) {
+    return lv_obj_has_flag((lv_obj_t *)obj, LV_OBJ_FLAG_HIDDEN);
+}
+
+bool capi_obj_is_disabled(void *obj) {
+    return lv_obj_has_state((lv_obj_t *)obj, LV_STATE_DISABLED);
+}
+
+void capi_obj_set_hidden(void *obj, bool hidden) {
+    if (!obj) return;
+    if (hidden) lv_obj_add_flag((lv_obj_t *)obj, LV_OBJ_FLAG_HIDDEN);
+    else lv_obj_clear_flag((lv_obj_t *)obj, LV_OBJ_FLAG_HIDDEN);
+}
+
+void capi_obj_set_disabled(void *obj, bool disabled) {
+    if (!obj) return;
+    if (disabled) lv_obj_add_state((lv_obj_t *)obj, LV_STATE_DISABLED);
+    else lv_obj_clear_state((lv_obj_t *)obj, LV_STATE_DISABLED);
+}
+
+// delete lvgl object and its children, pending events are dropped
+void capi_obj_del(void *obj) {
+    if (!obj) return;
+    lv_obj_del((lv_obj_t *)obj);
+}
+
+uint32_t capi_obj_get_child_cnt(void *obj) {
+    return lv_obj_get_child_cnt((lv_obj_t *)obj);
+}
+
+void *capi_obj_get_child(void *obj, uint32_t index) {
+    return lv_obj_get_child((lv_obj_t *)obj, (int32_t)index);
+}
+
+// geometry is returned in screen absolute coordinates
+void capi_obj_get_coords(void *obj, int16_t *x, int16_t *y, int16_t *width, int16_t *height) {
+    lv_area_t area;
+    lv_obj_update_layout((lv_obj_t *)obj);
+    lv_obj_get_coords((lv_obj_t *)obj, &area);
+    *x = area.x1;
+    *y = area.y1;
+    *width = lv_area_get_width(&area);
+    *height = lv_area_get_height(&area);
+}
+
+int32_t capi_bar_get_value(void *obj) {
+    return lv_bar_get_value((lv_obj_t *)obj);
+}
+
+int32_t capi_arc_get_value(void *obj) {
+    return lv_arc_get_value((lv_obj_t *)obj);
+}
+
+// lvgl has no meter getter, value is read back from first indicator (needle or arc)
+int32_t capi_meter_get_value(void *obj) {
+    lv_meter_t *meter = (lv_meter_t *)obj;
+    lv_meter_indicator_t *indic;
+    // value is held by needle, other indicators (arc, scale lines) are static zones
+    _LV_LL_READ(&meter->indicator_ll, indic) {
+        if (indic->type == LV_METER_INDICATOR_TYPE_NEEDLE_LINE ||
+            indic->type == LV_METER_INDICATOR_TYPE_NEEDLE_IMG)
+            return indic->start_value;
+    }
+    return 0;
+}
+
+uint8_t capi_led_get_brightness(void *obj) {
+    return lv_led_get_brightness((lv_obj_t *)obj);
+}
+
+const char *capi_label_get_text(void *obj) {
+    return lv_label_get_text((lv_obj_t *)obj);
+}
+
+const char *capi_textarea_get_text(void *obj) {
+    return lv_textarea_get_text((lv_obj_t *)obj);
+}
+
+// button text is held by its first label child
+const char *capi_button_get_text(void *obj) {
+    uint32_t count = lv_obj_get_child_cnt((lv_obj_t *)obj);
+    for (uint32_t idx = 0; idx < count; idx++) {
+        lv_obj_t *child = lv_obj_get_child((lv_obj_t *)obj, idx);
+        if (lv_obj_check_type(child, &lv_label_class)) return lv_label_get_text(child);
+    }
+    return NULL;
+}
+
+
+
+// screen container: full display area without decoration, only one screen is visible at a time
+void capi_screen_init(void *obj, bool hidden) {
+    lv_obj_set_style_pad_all((lv_obj_t *)obj, 0, 0);
+    lv_obj_set_style_border_width((lv_obj_t *)obj, 0, 0);
+    lv_obj_set_style_radius((lv_obj_t *)obj, 0, 0);
+    lv_obj_clear_flag((lv_obj_t *)obj, LV_OBJ_FLAG_SCROLLABLE);
+    if (hidden) lv_obj_add_flag((lv_obj_t *)obj, LV_OBJ_FLAG_HIDDEN);
+}
+
+// transition codes should match display-lvgl.rs ScreenTransition
+#define CAPI_SCREEN_NONE 0
+#define CAPI_SCREEN_FADE 1
+#define CAPI_SCREEN_SLIDE_LEFT 2
+#define CAPI_SCREEN_SLIDE_RIGHT 3
+
+static void screen_anim_x_cb(void *obj, int32_t value) {
+    lv_obj_set_x((lv_obj_t *)obj, (lv_coord_t)value);
+}
+
+static void screen_anim_opa_cb(void *obj, int32_t value) {
+    lv_obj_set_style_opa((lv_obj_t *)obj, (lv_opa_t)value, 0);
+}
+
+// once out of sight previous screen is hidden and restored to its default position
+static void screen_hide_ready_cb(lv_anim_t *anim) {
+    lv_obj_t *obj = (lv_obj_t *)anim->var;
+    lv_obj_add_flag(obj, LV_OBJ_FLAG_HIDDEN);
+    lv_obj_set_x(obj, 0);
+    lv_obj_set_style_opa(obj, LV_OPA_COVER, 0);
+}
+
+static void screen_anim_start(lv_obj_t *obj, lv_anim_exec_xcb_t exec_cb, int32_t start, int32_t end, uint32_t time, lv_anim_ready_cb_t ready_cb) {
+    lv_anim_t anim;
+    lv_anim_init(&anim);
+    lv_anim_set_var(&anim, obj);
+    lv_anim_set_values(&anim, start, end);
+    lv_anim_set_time(&anim, time);
+    lv_anim_set_exec_cb(&anim, exec_cb);
+    if (ready_cb) lv_anim_set_ready_cb(&anim, ready_cb);
+    lv_anim_start(&anim);
+}
+
+void capi_screen_switch(void *from, void *to, int transition, uint32_t time) {
+    lv_obj_t *previous = (lv_obj_t *)from;
+    lv_obj_t *next = (lv_obj_t *)to;
+    lv_coord_t width = lv_obj_get_width(previous);
+
+    lv_obj_clear_flag(next, LV_OBJ_FLAG_HIDDEN);
+    lv_obj_move_foreground(next);
+
+    if (time == 0) transition = CAPI_SCREEN_NONE;
+    switch (transition) {
+    case CAPI_SCREEN_FADE:
+        screen_anim_start(next, screen_anim_opa_cb, LV_OPA_TRANSP, LV_OPA_COVER, time, NULL);
+        screen_anim_start(previous, screen_anim_opa_cb, LV_OPA_COVER, LV_OPA_TRANSP, time, screen_hide_ready_cb);
+        break;
+    case CAPI_SCREEN_SLIDE_LEFT:
+        screen_anim_start(next, screen_anim_x_cb, width, 0, time, NULL);
+        screen_anim_start(previous, screen_anim_x_cb, 0, -width, time, screen_hide_ready_cb);
+        break;
+    case CAPI_SCREEN_SLIDE_RIGHT:
+        screen_anim_start(next, screen_anim_x_cb, -width, 0, time, NULL);
+        screen_anim_start(previous, screen_anim_x_cb, 0, width, time, screen_hide_ready_cb);
+        break;
+    default:
+        lv_obj_add_flag(previous, LV_OBJ_FLAG_HIDDEN);
+        break;
+    }
+}
+
+// lvgl-rclib native init opens fbdev or GTK window, it is skipped when display renders offscreen or to drm.
+// Calls are redirected here by linker (-Wl,--wrap, cf: build.rs), real functions are weak as only one driver is built.
+static bool capi_native_skip = false;
+
+void capi_native_set_skip(bool skip) {
+    capi_native_skip = skip;
+}
+
+extern void __real_fbdev_init(void) __attribute__((weak));
+extern void __real_gtkdrv_init(void) __attribute__((weak));
+
+void __wrap_fbdev_init(void) {
+    if (!capi_native_skip && __real_fbdev_init) __real_fbdev_init();
+}
+
+void __wrap_gtkdrv_init(void) {
+    if (!capi_native_skip && __real_gtkdrv_init) __real_gtkdrv_init();
+}
+
+// offscreen backend: redirect default display flush into a RAM frame, no framebuffer or X display needed
+static lv_color_t *offscreen_frame = NULL;
+static lv_coord_t offscreen_width = 0;
+static lv_coord_t offscreen_height = 0;
+
+static void offscreen_flush_cb(lv_disp_drv_t *drv, const lv_area_t *area, lv_color_t *color_p) {
+    lv_coord_t width = lv_area_get_width(area);
+
+    for (lv_coord_t y = area->y1; y <= area->y2; y++) {
+        if (y >= 0 && y < offscreen_height) {
+            for (lv_coord_t x = area->x1; x <= area->x2; x++) {
+                if (x >= 0 && x < offscreen_width) {
+                    offscreen_frame[y * offscreen_width + x] = color_p[(y - area->y1) * width + (x - area->x1)];
+                }
+            }
+        }
+    }
+    lv_disp_flush_ready(drv);
+}
+
+int capi_offscreen_init(void) {
+    lv_disp_t *disp = lv_disp_get_default();
+    if (!disp) return -1;
+
+    offscreen_width = lv_disp_get_hor_res(disp);
+    offscreen_height = lv_disp_get_ver_res(disp);
+    offscreen_frame = calloc(offscreen_width * offscreen_height, sizeof(lv_color_t));
+    if (!offscreen_frame) return -1;
+
+    disp->driver->flush_cb = offscreen_flush_cb;
+    lv_obj_invalidate(lv_scr_act());
+    return 0;
+}
+
+// DRM/KMS backend (cargo feature 'drm'): lvgl areas are copied into the back dumb buffer, which is page flipped
+// on last flush of each frame. Display resolution should match selected mode, extra pixels are clipped.
+#ifndef USE_DRM
+#define USE_DRM 0
+#endif
+
+#if USE_DRM
+#include <poll.h>
+#include <stdio.h>
+#include <sys/mman.h>
+#include <xf86drm.h>
+#include <xf86drmMode.h>
+
+typedef struct {
+    uint32_t handle;
+    uint32_t fb_id;
+    uint32_t pitch;
+    uint64_t size;
+    uint8_t *map;
+} capi_drm_buffer_t;
+
+static struct {
+    int fd;
+    uint32_t connector_id;
+    char connector[32];
+    uint32_t crtc_id;
+    drmModeModeInfo mode;
+    drmModeCrtc *saved_crtc;
+    capi_drm_buffer_t buffers[2];
+    int front;
+    bool flip_pending;
+} capi_drm = {.fd = -1};
+
+// kernel connector names, indexed by DRM_MODE_CONNECTOR_xxx
+static const char *capi_drm_connector_types[] = {
+    "Unknown", "VGA", "DVI-I", "DVI-D", "DVI-A", "Composite", "SVIDEO", "LVDS", "Component", "DIN", "DP",
+    "HDMI-A", "HDMI-B", "TV", "eDP", "Virtual", "DSI", "DPI", "Writeback", "SPI", "USB",
+};
+
+static void capi_drm_connector_name(drmModeConnector *connector, char *name, size_t len) {
+    const char *type = "Unknown";
+    if (connector->connector_type < sizeof(capi_drm_connector_types) / sizeof(capi_drm_connector_types[0])) {
+        type = capi_drm_connector_types[connector->connector_type];
+    }
+    snprintf(name, len, "%s-%u", type, connector->connector_type_id);
+}
+
+// mode is "WxH" or "WxH@R", when missing use display resolution then connector preferred mode
+static int capi_drm_select_mode(drmModeConnector *connector, const char *name, drmModeModeInfo *mode) {
+    int width = lv_disp_get_hor_res(NULL), height = lv_disp_get_ver_res(NULL), refresh = 0;
+    if (name && sscanf(name, "%dx%d@%d", &width, &height, &refresh) < 2) return -EINVAL;
+
+    drmModeModeInfo *preferred = NULL;
+    for (int idx = 0; idx < connector->count_modes; idx++) {
+        drmModeModeInfo *info = &connector->modes[idx];
+        if (info->hdisplay == width && info->vdisplay == height && (refresh == 0 || info->vrefresh == (uint32_t)refresh)) {
+            *mode = *info;
+            return 0;
+        }
+        if (!preferred && (info->type & DRM_MODE_TYPE_PREFERRED)) preferred = info;
+    }
+    if (name) return -EINVAL;
+    if (!preferred && connector->count_modes > 0) preferred = &connector->modes[0];
+    if (!preferred) return -EINVAL;
+    *mode = *preferred;
+    return 0;
+}
+
+// keep current connector crtc when any, else pick first crtc compatible with one of connector encoders
+static uint32_t capi_drm_select_crtc(drmModeRes *resources, drmModeConnector *connector) {
+    if (connector->encoder_id) {
+        drmModeEncoder *encoder = drmModeGetEncoder(capi_drm.fd, connector->encoder_id);
+        if (encoder) {
+            uint32_t crtc_id = encoder->crtc_id;
+            drmModeFreeEncoder(encoder);
+            if (crtc_id) return crtc_id;
+        }
+    }
+    for (int idx = 0; idx < connector->count_encoders; idx++) {
+        drmModeEncoder *encoder = drmModeGetEncoder(capi_drm.fd, connector->encoders[idx]);
+        if (!encoder) continue;
+        for (int jdx = 0; jdx < resources->count_crtcs; jdx++) {
+            if (encoder->possible_crtcs & (1u << jdx)) {
+                drmModeFreeEncoder(encoder);
+                return resources->crtcs[jdx];
+            }
+        }
+        drmModeFreeEncoder(encoder);
+    }
+    return 0;
+}
+
+static int capi_drm_create_buffer(capi_drm_buffer_t *buffer) {
+    struct drm_mode_create_dumb create = {
+        .width = capi_drm.mode.hdisplay,
+        .height = capi_drm.mode.vdisplay,
+        .bpp = 32,
+    };
+    if (drmIoctl(capi_drm.fd, DRM_IOCTL_MODE_CREATE_DUMB, &create) < 0) return -errno;
+    buffer->handle = create.handle;
+    buffer->pitch = create.pitch;
+    buffer->size = create.size;
+
+    if (drmModeAddFB(capi_drm.fd, create.width, create.height, 24, 32, buffer->pitch, buffer->handle, &buffer->fb_id)) {
+        return -errno;
+    }
+
+    struct drm_mode_map_dumb map = {.handle = buffer->handle};
+    if (drmIoctl(capi_drm.fd, DRM_IOCTL_MODE_MAP_DUMB, &map) < 0) return -errno;
+    buffer->map = mmap(NULL, buffer->size, PROT_READ | PROT_WRITE, MAP_SHARED, capi_drm.fd, map.offset);
+    if (buffer->map == MAP_FAILED) {
+        buffer->map = NULL;
+        return -errno;
+    }
+    memset(buffer->map, 0, buffer->size);
+    return 0;
+}
+
+// give back screen to previous owner (ex: fbcon) when process exits
+static void capi_drm_restore(void) {
+    drmModeCrtc *saved = capi_drm.saved_crtc;
+    if (!saved) return;
+    drmModeSetCrtc(capi_drm.fd, saved->crtc_id, saved->buffer_id, saved->x, saved->y, &capi_drm.connector_id, 1,
+                   &saved->mode);
+    drmModeFreeCrtc(saved);
+    capi_drm.saved_crtc = NULL;
+}
+
+static void capi_drm_flip_handler(int fd, unsigned int sequence, unsigned int sec, unsigned int usec, void *data) {
+    capi_drm.flip_pending = false;
+}
+
+// show back buffer at next vblank, falls back to a direct crtc update when page flip is not supported
+static void capi_drm_flip(void) {
+    int back = capi_drm.front ^ 1;
+    if (drmModePageFlip(capi_drm.fd, capi_drm.crtc_id, capi_drm.buffers[back].fb_id, DRM_MODE_PAGE_FLIP_EVENT, NULL) ==
+        0) {
+        drmEventContext context = {.version = 2, .page_flip_handler = capi_drm_flip_handler};
+        struct pollfd pollfd = {.fd = capi_drm.fd, .events = POLLIN};
+        capi_drm.flip_pending = true;
+        while (capi_drm.flip_pending && poll(&pollfd, 1, 100) > 0) {
+            drmHandleEvent(capi_drm.fd, &context);
+        }
+    } else {
+        drmModeSetCrtc(capi_drm.fd, capi_drm.crtc_id, capi_drm.buffers[back].fb_id, 0, 0, &capi_drm.connector_id, 1,
+                       &capi_drm.mode);
+    }
+    capi_drm.front = back;
+
+    // lvgl only redraws invalidated areas, next frame starts from displayed one
+    memcpy(capi_drm.buffers[back ^ 1].map, capi_drm.buffers[back].map, capi_drm.buffers[back].size);
+}
+
+static void capi_drm_flush_cb(lv_disp_drv_t *drv, const lv_area_t *area, lv_color_t *color_p) {
+    capi_drm_buffer_t *buffer = &capi_drm.buffers[capi_drm.front ^ 1];
+    lv_coord_t width = lv_area_get_width(area);
+
+    for (lv_coord_t y = area->y1; y <= area->y2; y++) {
+        if (y < 0 || y >= capi_drm.mode.vdisplay) continue;
+        uint32_t *row = (uint32_t *)(buffer->map + (size_t)y * buffer->pitch);
+        for (lv_coord_t x = area->x1; x <= area->x2; x++) {
+            if (x >= 0 && x < capi_drm.mode.hdisplay) {
+                row[x] = lv_color_to32(color_p[(y - area->y1) * width + (x - area->x1)]);
+            }
+        }
+    }
+    if (lv_disp_flush_is_last(drv)) capi_drm_flip();
+    lv_disp_flush_ready(drv);
+}
+
+// connector is a kernel name (HDMI-A-1, DSI-1, Virtual-1), first connected one when missing
+int capi_drm_init(const char *device, const char *connector_name, const char *mode_name) {
+    lv_disp_t *disp = lv_disp_get_default();
+    if (!disp) return -ENODEV;
+    if (capi_drm.fd >= 0) return -EBUSY;
+
+    capi_drm.fd = open(device, O_RDWR | O_CLOEXEC);
+    if (capi_drm.fd < 0) return -errno;
+
+    int status = -ENODEV;
+    drmModeRes *resources = drmModeGetResources(capi_drm.fd);
+    drmModeConnector *connector = NULL;
+    if (!resources) goto OnErrorExit;
+
+    for (int idx = 0; idx < resources->count_connectors; idx++) {
+        drmModeConnector *candidate = drmModeGetConnector(capi_drm.fd, resources->connectors[idx]);
+        if (!candidate) continue;
+        char name[32];
+        capi_drm_connector_name(candidate, name, sizeof(name));
+        if ((connector_name && strcmp(name, connector_name) == 0) ||
+            (!connector_name && candidate->connection == DRM_MODE_CONNECTED)) {
+            connector = candidate;
+            snprintf(capi_drm.connector, sizeof(capi_drm.connector), "%s", name);
+            break;
+        }
+        drmModeFreeConnector(candidate);
+    }
+    if (!connector) goto OnErrorExit;
+    capi_drm.connector_id = connector->connector_id;
+
+    status = capi_drm_select_mode(connector, mode_name, &capi_drm.mode);
+    if (status < 0) goto OnErrorExit;
+
+    status = -ENODEV;
+    capi_drm.crtc_id = capi_drm_select_crtc(resources, connector);
+    if (!capi_drm.crtc_id) goto OnErrorExit;
+
+    for (int idx = 0; idx < 2; idx++) {
+        status = capi_drm_create_buffer(&capi_drm.buffers[idx]);
+        if (status < 0) goto OnErrorExit;
+    }
+
+    capi_drm.saved_crtc = drmModeGetCrtc(capi_drm.fd, capi_drm.crtc_id);
+    capi_drm.front = 0;
+    if (drmModeSetCrtc(capi_drm.fd, capi_drm.crtc_id, capi_drm.buffers[0].fb_id, 0, 0, &capi_drm.connector_id, 1,
+                       &capi_drm.mode)) {
+        status = -errno;
+        goto OnErrorExit;
+    }
+    atexit(capi_drm_restore);
+
+    drmModeFreeConnector(connector);
+    drmModeFreeResources(resources);
+    disp->driver->flush_cb = capi_drm_flush_cb;
+    lv_obj_invalidate(lv_scr_act());
+    return 0;
+
+OnErrorExit:
+    if (connector) drmModeFreeConnector(connector);
+    if (resources) drmModeFreeResources(resources);
+    if (capi_drm.saved_crtc) drmModeFreeCrtc(capi_drm.saved_crtc);
+    capi_drm.saved_crtc = NULL;
+    for (int idx = 0; idx < 2; idx++) {
+        capi_drm_buffer_t *buffer = &capi_drm.buffers[idx];
+        if (buffer->map) munmap(buffer->map, buffer->size);
+        if (buffer->fb_id) drmModeRmFB(capi_drm.fd, buffer->fb_id);
+        if (buffer->handle) {
+            struct drm_mode_destroy_dumb destroy = {.handle = buffer->handle};
+            drmIoctl(capi_drm.fd, DRM_IOCTL_MODE_DESTROY_DUMB, &destroy);
+        }
+        memset(buffer, 0, sizeof(capi_drm_buffer_t));
+    }
+    close(capi_drm.fd);
+    capi_drm.fd = -1;
+    return status;
+}
+
+// selected connector and mode
+int capi_drm_get_mode(char *connector, size_t len, uint16_t *width, uint16_t *height, uint32_t *refresh) {
+    if (capi_drm.fd < 0) return -1;
+    snprintf(connector, len, "%s", capi_drm.connector);
+    *width = capi_drm.mode.hdisplay;
+    *height = capi_drm.mode.vdisplay;
+    *refresh = capi_drm.mode.vrefresh;
+    return 0;
+}
+
+// copy displayed buffer as RGBA8888 (same layout as capi_offscreen_read)
+int capi_drm_read(uint8_t *rgba, size_t len, int16_t *width, int16_t *height) {
+    if (capi_drm.fd < 0) return -1;
+    capi_drm_buffer_t *buffer = &capi_drm.buffers[capi_drm.front];
+    *width = LV_MIN(lv_disp_get_hor_res(NULL), capi_drm.mode.hdisplay);
+    *height = LV_MIN(lv_disp_get_ver_res(NULL), capi_drm.mode.vdisplay);
+    if (len < (size_t)*width * *height * 4) return -1;
+
+    size_t idx = 0;
+    for (lv_coord_t y = 0; y < *height; y++) {
+        uint32_t *row = (uint32_t *)(buffer->map + (size_t)y * buffer->pitch);
+        for (lv_coord_t x = 0; x < *width; x++, idx++) {
+            rgba[idx * 4 + 0] = (row[x] >> 16) & 0xFF;
+            rgba[idx * 4 + 1] = (row[x] >> 8) & 0xFF;
+            rgba[idx * 4 + 2] = row[x] & 0xFF;
+            rgba[idx * 4 + 3] = 0xFF;
+        }
+    }
+    return 0;
+}
+#else
+int capi_drm_init(const char *device, const char *connector_name, const char *mode_name) {
+    return -ENOTSUP;
+}
+
+int capi_drm_get_mode(char *connector, size_t len, uint16_t *width, uint16_t *height, uint32_t *refresh) {
+    return -1;
+}
+
+int capi_drm_read(uint8_t *rgba, size_t len, int16_t *width, int16_t *height) {
+    return -1;
+}
+#endif
+
+// render pending invalidated areas immediately, should not run concurrently with lvgl main loop
+void capi_refresh_now(void) {
+    lv_refr_now(NULL);
+}
+
+// copy offscreen frame as RGBA8888, buffer should be width*height*4 long
+int capi_offscreen_read(uint8_t *rgba, size_t len, int16_t *width, int16_t *height) {
+    if (!offscreen_frame) return -1;
+    *width = offscreen_width;
+    *height = offscreen_height;
+    if (len < (size_t)offscreen_width * offscreen_height * 4) return -1;
+
+    for (size_t idx = 0; idx < (size_t)offscreen_width * offscreen_height; idx++) {
+        uint32_t pixel = lv_color_to32(offscreen_frame[idx]);
+        rgba[idx * 4 + 0] = (pixel >> 16) & 0xFF;
+        rgba[idx * 4 + 1] = (pixel >> 8) & 0xFF;
+        rgba[idx * 4 + 2] = pixel & 0xFF;
+        rgba[idx * 4 + 3] = 0xFF;
+    }
+    return 0;
+}
+
+// capture object and its children as RGBA8888, returned buffer should be released with capi_snapshot_free
+uint8_t *capi_snapshot_take(void *obj, int16_t *width, int16_t *height) {
+    if (!obj) return NULL;
+    lv_img_dsc_t *snapshot = lv_snapshot_take((lv_obj_t *)obj, LV_IMG_CF_TRUE_COLOR);
+    if (!snapshot) return NULL;
+
+    *width = snapshot->header.w;
+    *height = snapshot->header.h;
+    uint8_t *rgba = malloc((size_t)snapshot->header.w * snapshot->header.h * 4);
+    if (rgba) {
+        size_t idx = 0;
+        for (lv_coord_t y = 0; y < snapshot->header.h; y++) {
+            for (lv_coord_t x = 0; x < snapshot->header.w; x++) {
+                uint32_t pixel = lv_color_to32(lv_img_buf_get_px_color(snapshot, x, y, lv_color_black()));
+                rgba[idx++] = (pixel >> 16) & 0xFF;
+                rgba[idx++] = (pixel >> 8) & 0xFF;
+                rgba[idx++] = pixel & 0xFF;
+                rgba[idx++] = 0xFF;
+            }
+        }
+    }
+    lv_snapshot_free(snapshot);
+    return rgba;
+}
+
+void capi_snapshot_free(uint8_t *rgba) {
+    free(rgba);
+}
+
+// periodic callback running within lvgl main loop thread
+typedef void (*capi_timer_cb_t)(void *context);
+
+typedef struct {
+    capi_timer_cb_t callback;
+    void *context;
+} capi_timer_ctx_t;
+
+static void capi_timer_handler(lv_timer_t *timer) {
+    capi_timer_ctx_t *ctx = (capi_timer_ctx_t *)timer->user_data;
+    ctx->callback(ctx->context);
+}
+
+// new timers are inserted first in lvgl timer list, a ready timer runs first on next lv_timer_handler call
+int capi_timer_create(capi_timer_cb_t callback, uint32_t period, void *context) {
+    capi_timer_ctx_t *ctx = malloc(sizeof(capi_timer_ctx_t));
+    if (!ctx) return -1;
+    ctx->callback = callback;
+    ctx->context = context;
+    lv_timer_t *timer = lv_timer_create(capi_timer_handler, period, ctx);
+    if (!timer) {
+        free(ctx);
+        return -1;
+    }
+    lv_timer_ready(timer);
+    return 0;
+}
+
+// runtime theme switch, default theme styles are updated in place then every object is refreshed
+int capi_theme_apply(uint8_t pred, uint8_t pgreen, uint8_t pblue, uint8_t sred, uint8_t sgreen, uint8_t sblue, bool dark, uint16_t font_size) {
+    const lv_font_t *font;
+    switch (font_size) {
+        case 10: font = &lv_font_montserrat_10; break;
+        case 14: font = &lv_font_montserrat_14; break;
+        case 18: font = &lv_font_montserrat_18; break;
+        case 22: font = &lv_font_montserrat_22; break;
+        default: return -1;
+    }
+
+    lv_disp_t *disp = lv_disp_get_default();
+    if (!disp) return -1;
+    lv_theme_t *theme = lv_theme_default_init(disp, lv_color_make(pred, pgreen, pblue), lv_color_make(sred, sgreen, sblue), dark, font);
+    lv_disp_set_theme(disp, theme);
+    lv_obj_report_style_change(NULL);
+    lv_obj_invalidate(lv_disp_get_scr_act(disp));
+    return 0;
+}
+
+// runtime image, rgba8888 is converted to LV_IMG_CF_TRUE_COLOR_ALPHA for current LV_COLOR_DEPTH
+void *capi_img_dsc_create(const uint8_t *rgba, uint32_t width, uint32_t height, size_t *size) {
+    size_t pixel = LV_IMG_PX_SIZE_ALPHA_BYTE;
+    size_t len = (size_t)width * height * pixel;
+    lv_img_dsc_t *dsc = calloc(1, sizeof(lv_img_dsc_t));
+    uint8_t *data = malloc(len);
+    if (!dsc || !data) {
+        free(dsc);
+        free(data);
+        return NULL;
+    }
+
+    for (size_t idx = 0; idx < (size_t)width * height; idx++) {
+        const uint8_t *src = &rgba[idx * 4];
+        lv_color_t color = lv_color_make(src[0], src[1], src[2]);
+        memcpy(&data[idx * pixel], &color, LV_COLOR_SIZE / 8);
+        data[idx * pixel + pixel - 1] = src[3];
+    }
+
+    dsc->header.cf = LV_IMG_CF_TRUE_COLOR_ALPHA;
+    dsc->header.always_zero = 0;
+    dsc->header.w = width;
+    dsc->header.h = height;
+    dsc->data_size = len;
+    dsc->data = data;
+    *size = len + sizeof(lv_img_dsc_t);
+    return dsc;
+}
+
+void capi_img_dsc_free(void *dsc) {
+    lv_img_cache_invalidate_src(dsc);
+    free((void *)((lv_img_dsc_t *)dsc)->data);
+    free(dsc);
+}
+
+// set image source on an img/imgbtn object or on its first img child
+int capi_img_set_src(void *obj, const void *dsc) {
+    lv_obj_t *target = (lv_obj_t *)obj;
+    if (lv_obj_check_type(target, &lv_imgbtn_class)) {
+        lv_imgbtn_set_src(target, LV_IMGBTN_STATE_RELEASED, NULL, dsc, NULL);
+        lv_imgbtn_set_src(target, LV_IMGBTN_STATE_PRESSED, NULL, dsc, NULL);
+        return 0;
+    }
+    if (!lv_obj_check_type(target, &lv_img_class)) {
+        target = NULL;
+        for (uint32_t idx = 0; idx < lv_obj_get_child_cnt(obj); idx++) {
+            lv_obj_t *child = lv_obj_get_child(obj, idx);
+            if (lv_obj_check_type(child, &lv_img_class)) {
+                target = child;
+                break;
+            }
+        }
+        if (!target) return -1;
+    }
+    lv_img_set_src(target, dsc);
+    return 0;
+}
+
+// journal replay: send lvgl event from its normalized name (lowercase, no '_'), ex: clicked, valuechanged
+static const struct {
+    const char *name;
+    lv_event_code_t code;
+} capi_events[] = {
+    {"pressed", LV_EVENT_PRESSED},
+    {"pressing", LV_EVENT_PRESSING},
+    {"presslost", LV_EVENT_PRESS_LOST},
+    {"shortclicked", LV_EVENT_SHORT_CLICKED},
+    {"longpressed", LV_EVENT_LONG_PRESSED},
+    {"longpressedrepeat", LV_EVENT_LONG_PRESSED_REPEAT},
+    {"clicked", LV_EVENT_CLICKED},
+    {"released", LV_EVENT_RELEASED},
+    {"focused", LV_EVENT_FOCUSED},
+    {"defocused", LV_EVENT_DEFOCUSED},
+    {"leave", LV_EVENT_LEAVE},
+    {"valuechanged", LV_EVENT_VALUE_CHANGED},
+    {"insert", LV_EVENT_INSERT},
+    {"refresh", LV_EVENT_REFRESH},
+    {"ready", LV_EVENT_READY},
+    {"cancel", LV_EVENT_CANCEL},
+};
+
+int capi_event_send(void *obj, const char *name) {
+    if (!obj || !name) return -1;
+    for (size_t idx = 0; idx < sizeof(capi_events) / sizeof(capi_events[0]); idx++) {
+        if (strcmp(capi_events[idx].name, name) == 0) {
+            lv_event_send((lv_obj_t *)obj, capi_events[idx].code, NULL);
+            return 0;
+        }
+    }
+    return -1;
+}
+
+// virtual input devices for remote testing, samples are queued from lvgl thread and consumed by indev read callbacks.
+// Each sample holds its state for 'hold' ms, indev read timer replays timed sequences without blocking any thread.
+#define CAPI_INPUT_QUEUE 128
+
+typedef struct {
+    lv_coord_t x;
+    lv_coord_t y;
+    uint32_t key;
+    bool pressed;
+    uint32_t hold;
+} capi_input_sample_t;
+
+typedef struct {
+    capi_input_sample_t samples[CAPI_INPUT_QUEUE];
+    uint32_t head;
+    uint32_t count;
+    capi_input_sample_t last;
+    // tick at which next sample may be read
+    uint32_t due;
+    lv_indev_drv_t driver;
+    lv_indev_t *indev;
+} capi_input_queue_t;
+
+static capi_input_queue_t capi_pointer;
+static capi_input_queue_t capi_keypad;
+static lv_group_t *capi_input_group = NULL;
+
+static bool capi_input_is_due(capi_input_queue_t *queue, uint32_t now) {
+    return (int32_t)(now - queue->due) >= 0;
+}
+
+static int capi_input_push(capi_input_queue_t *queue, capi_input_sample_t sample) {
+    if (queue->count >= CAPI_INPUT_QUEUE) return -1;
+    uint32_t now = lv_tick_get();
+    if (queue->count == 0 && capi_input_is_due(queue, now)) queue->due = now;
+    queue->samples[(queue->head + queue->count) % CAPI_INPUT_QUEUE] = sample;
+    queue->count++;
+    return 0;
+}
+
+// one sample per read, lvgl keeps reading while due samples are pending, last state is repeated until next one is due.
+// Late reads catch up from scheduled time, sequence keeps its overall duration.
+static void capi_input_pop(capi_input_queue_t *queue, lv_indev_data_t *data) {
+    uint32_t now = lv_tick_get();
+    if (queue->count > 0 && capi_input_is_due(queue, now)) {
+        queue->last = queue->samples[queue->head];
+        queue->head = (queue->head + 1) % CAPI_INPUT_QUEUE;
+        queue->count--;
+        queue->due += queue->last.hold;
+    }
+    data->point.x = queue->last.x;
+    data->point.y = queue->last.y;
+    data->key = queue->last.key;
+    data->state = queue->last.pressed ? LV_INDEV_STATE_PRESSED : LV_INDEV_STATE_RELEASED;
+    data->continue_reading = queue->count > 0 && capi_input_is_due(queue, now);
+}
+
+static void capi_pointer_read_cb(lv_indev_drv_t *driver, lv_indev_data_t *data) {
+    capi_input_pop(&capi_pointer, data);
+}
+
+static void capi_keypad_read_cb(lv_indev_drv_t *driver, lv_indev_data_t *data) {
+    capi_input_pop(&capi_keypad, data);
+}
+
+int capi_input_init(void) {
+    if (capi_pointer.indev) return 0;
+
+    lv_indev_drv_init(&capi_pointer.driver);
+    capi_pointer.driver.type = LV_INDEV_TYPE_POINTER;
+    capi_pointer.driver.read_cb = capi_pointer_read_cb;
+    capi_pointer.indev = lv_indev_drv_register(&capi_pointer.driver);
+
+    lv_indev_drv_init(&capi_keypad.driver);
+    capi_keypad.driver.type = LV_INDEV_TYPE_KEYPAD;
+    capi_keypad.driver.read_cb = capi_keypad_read_cb;
+    capi_keypad.indev = lv_indev_drv_register(&capi_keypad.driver);
+
+    if (!capi_pointer.indev || !capi_keypad.indev) {
+        // keep init retryable, early return above checks pointer indev only
+        if (capi_pointer.indev) lv_indev_delete(capi_pointer.indev);
+        if (capi_keypad.indev) lv_indev_delete(capi_keypad.indev);
+        capi_pointer.indev = NULL;
+        capi_keypad.indev = NULL;
+        return -1;
+    }
+    capi_input_group = lv_group_create();
+    lv_indev_set_group(capi_keypad.indev, capi_input_group);
+    return 0;
+}
+
+int capi_input_pointer(int16_t x, int16_t y, bool pressed, uint32_t hold) {
+    capi_input_sample_t sample = {.x = x, .y = y, .key = 0, .pressed = pressed, .hold = hold};
+    return capi_input_push(&capi_pointer, sample);
+}
+
+int capi_input_key(uint32_t key, bool pressed, uint32_t hold) {
+    capi_input_sample_t sample = {.x = 0, .y = 0, .key = key, .pressed = pressed, .hold = hold};
+    return capi_input_push(&capi_keypad, sample);
+}
+
+// free queue slots, a sequence is only queued when it fits as a whole
+uint32_t capi_input_free(bool keypad) {
+    return CAPI_INPUT_QUEUE - (keypad ? capi_keypad.count : capi_pointer.count);
+}
+
+// keys are sent to focused object of virtual keypad group
+int capi_input_focus(void *obj) {
+    if (!obj || !capi_input_group) return -1;
+    if (lv_obj_get_group((lv_obj_t *)obj) != capi_input_group) {
+        lv_group_add_obj(capi_input_group, (lv_obj_t *)obj);
+    }
+    lv_group_focus_obj((lv_obj_t *)obj);
+    return 0;
+}
+
+// lvgl control keys, 0 when name is unknown
+uint32_t capi_input_key_code(const char *name) {
+    static const struct {
+        const char *name;
+        uint32_t key;
+    } keys[] = {
+        {"up", LV_KEY_UP},
+        {"down", LV_KEY_DOWN},
+        {"right", LV_KEY_RIGHT},
+        {"left", LV_KEY_LEFT},
+        {"esc", LV_KEY_ESC},
+        {"del", LV_KEY_DEL},
+        {"backspace", LV_KEY_BACKSPACE},
+        {"enter", LV_KEY_ENTER},
+        {"next", LV_KEY_NEXT},
+        {"prev", LV_KEY_PREV},
+        {"home", LV_KEY_HOME},
+        {"end", LV_KEY_END},
+    };
+    for (size_t idx = 0; idx < sizeof(keys) / sizeof(keys[0]); idx++) {
+        if (strcmp(keys[idx].name, name) == 0) return keys[idx].key;
+    }
+    return 0;
+}
+
+// evdev touchscreen and keyboard devices, read from lvgl thread with non blocking fd
+#define CAPI_EVDEV_MAX 8
+#define CAPI_EVDEV_TOUCH 0
+#define CAPI_EVDEV_KEYBOARD 1
+
+typedef struct {
+    int fd;
+    int kind;
+    lv_indev_drv_t driver;
+    lv_indev_t *indev;
+    // absolute axis range reported by the device
+    int32_t min_x, max_x, min_y, max_y;
+    // calibration: raw axis are normalized to 0..1, swapped/inverted, then mapped through matrix
+    bool swap_xy, invert_x, invert_y;
+    float matrix[6];
+    int32_t raw_x, raw_y;
+    bool pressed;
+    uint32_t key;
+} capi_evdev_t;
+
+static capi_evdev_t capi_evdevs[CAPI_EVDEV_MAX];
+static int capi_evdev_count = 0;
+
+// linux keycode to lvgl key, printable keys use us qwerty layout without shift
+static uint32_t capi_evdev_key(uint16_t code) {
+    static const char *rows[] = {"1234567890-=", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"};
+    static const uint16_t firsts[] = {KEY_1, KEY_Q, KEY_A, KEY_Z};
+    switch (code) {
+        case KEY_UP: return LV_KEY_UP;
+        case KEY_DOWN: return LV_KEY_DOWN;
+        case KEY_LEFT: return LV_KEY_LEFT;
+        case KEY_RIGHT: return LV_KEY_RIGHT;
+        case KEY_ESC: return LV_KEY_ESC;
+        case KEY_DELETE: return LV_KEY_DEL;
+        case KEY_BACKSPACE: return LV_KEY_BACKSPACE;
+        case KEY_ENTER:
+        case KEY_KPENTER: return LV_KEY_ENTER;
+        case KEY_TAB: return LV_KEY_NEXT;
+        case KEY_HOME: return LV_KEY_HOME;
+        case KEY_END: return LV_KEY_END;
+        case KEY_SPACE: return ' ';
+    }
+    for (size_t idx = 0; idx < sizeof(rows) / sizeof(rows[0]); idx++) {
+        if (code >= firsts[idx] && code < firsts[idx] + strlen(rows[idx])) return rows[idx][code - firsts[idx]];
+    }
+    return 0;
+}
+
+static lv_coord_t capi_evdev_scale(float value, lv_coord_t resolution) {
+    if (value < 0) value = 0;
+    if (value > 1) value = 1;
+    return (lv_coord_t)(value * (resolution - 1) + 0.5f);
+}
+
+static float capi_evdev_normalize(int32_t value, int32_t min, int32_t max) {
+    if (max <= min) return 0;
+    return (float)(value - min) / (float)(max - min);
+}
+
+static void capi_evdev_read_cb(lv_indev_drv_t *driver, lv_indev_data_t *data) {
+    capi_evdev_t *evdev = (capi_evdev_t *)driver->user_data;
+    struct input_event event;
+
+    while (read(evdev->fd, &event, sizeof(event)) == sizeof(event)) {
+        if (event.type == EV_ABS) {
+            if (event.code == ABS_X || event.code == ABS_MT_POSITION_X) evdev->raw_x = event.value;
+            else if (event.code == ABS_Y || event.code == ABS_MT_POSITION_Y) evdev->raw_y = event.value;
+            else if (event.code == ABS_MT_TRACKING_ID) evdev->pressed = event.value >= 0;
+        } else if (event.type == EV_KEY) {
+            if (event.code == BTN_TOUCH || event.code == BTN_LEFT) {
+                evdev->pressed = event.value != 0;
+            } else if (evdev->kind == CAPI_EVDEV_KEYBOARD) {
+                uint32_t key = capi_evdev_key(event.code);
+                if (key == 0 || event.value == 2) continue; // unmapped key or autorepeat
+                evdev->key = key;
+                evdev->pressed = event.value != 0;
+                // one key transition per read, lvgl reads again while events are pending
+                data->continue_reading = true;
+                break;
+            }
+        }
+    }
+
+    if (evdev->kind == CAPI_EVDEV_KEYBOARD) {
+        data->key = evdev->key;
+    } else {
+        float x = capi_evdev_normalize(evdev->raw_x, evdev->min_x, evdev->max_x);
+        float y = capi_evdev_normalize(evdev->raw_y, evdev->min_y, evdev->max_y);
+        if (evdev->swap_xy) {
+            float swap = x;
+            x = y;
+            y = swap;
+        }
+        if (evdev->invert_x) x = 1 - x;
+        if (evdev->invert_y) y = 1 - y;
+        float *matrix = evdev->matrix;
+        data->point.x = capi_evdev_scale(matrix[0] * x + matrix[1] * y + matrix[2], lv_disp_get_hor_res(driver->disp));
+        data->point.y = capi_evdev_scale(matrix[3] * x + matrix[4] * y + matrix[5], lv_disp_get_ver_res(driver->disp));
+    }
+    data->state = evdev->pressed ? LV_INDEV_STATE_PRESSED : LV_INDEV_STATE_RELEASED;
+}
+
+// return device index or -errno, keyboard devices are attached to virtual input keypad group
+int capi_evdev_open(const char *path, int kind) {
+    if (capi_evdev_count >= CAPI_EVDEV_MAX) return -ENOSPC;
+    int fd = open(path, O_RDONLY | O_NONBLOCK | O_CLOEXEC);
+    if (fd < 0) return -errno;
+
+    capi_evdev_t *evdev = &capi_evdevs[capi_evdev_count];
+    memset(evdev, 0, sizeof(capi_evdev_t));
+    evdev->fd = fd;
+    evdev->kind = kind;
+    evdev->matrix[0] = 1;
+    evdev->matrix[4] = 1;
+
+    lv_indev_drv_init(&evdev->driver);
+    if (kind == CAPI_EVDEV_KEYBOARD) {
+        // keyboard focus group must exist before indev is registered
+        if (capi_input_init() < 0) {
+            close(fd);
+            return -ENOMEM;
+        }
+        evdev->driver.type = LV_INDEV_TYPE_KEYPAD;
+    } else {
+        struct input_absinfo absinfo;
+        if (ioctl(fd, EVIOCGABS(ABS_X), &absinfo) == 0) {
+            evdev->min_x = absinfo.minimum;
+            evdev->max_x = absinfo.maximum;
+        }
+        if (ioctl(fd, EVIOCGABS(ABS_Y), &absinfo) == 0) {
+            evdev->min_y = absinfo.minimum;
+            evdev->max_y = absinfo.maximum;
+        }
+        if (evdev->max_x <= evdev->min_x || evdev->max_y <= evdev->min_y) {
+            close(fd);
+            return -EINVAL;
+        }
+        evdev->driver.type = LV_INDEV_TYPE_POINTER;
+    }
+    evdev->driver.read_cb = capi_evdev_read_cb;
+    evdev->driver.user_data = evdev;
+    evdev->indev = lv_indev_drv_register(&evdev->driver);
+    if (!evdev->indev) {
+        close(fd);
+        return -ENOMEM;
+    }
+
+    if (kind == CAPI_EVDEV_KEYBOARD) lv_indev_set_group(evdev->indev, capi_input_group);
+    return capi_evdev_count++;
+}
+
+// matrix holds 6 floats: x' = m0*x + m1*y + m2, y' = m3*x + m4*y + m5 (normalized coordinates)
+int capi_evdev_calibrate(int index, bool swap_xy, bool invert_x, bool invert_y, const float *matrix) {
+    if (index < 0 || index >= capi_evdev_count || capi_evdevs[index].kind != CAPI_EVDEV_TOUCH) return -1;
+    capi_evdev_t *evdev = &capi_evdevs[index];
+    evdev->swap_xy = swap_xy;
+    evdev->invert_x = invert_x;
+    evdev->invert_y = invert_y;
+    memcpy(evdev->matrix, matrix, sizeof(evdev->matrix));
+    return 0;
+}
+
+// last touch position, normalized before calibration
+int capi_evdev_get_raw(int index, float *x, float *y, bool *pressed) {
+    if (index < 0 || index >= capi_evdev_count || capi_evdevs[index].kind != CAPI_EVDEV_TOUCH) return -1;
+    capi_evdev_t *evdev = &capi_evdevs[index];
+    *x = capi_evdev_normalize(evdev->raw_x, evdev->min_x, evdev->max_x);
+    *y = capi_evdev_normalize(evdev->raw_y, evdev->min_y, evdev->max_y);
+    *pressed = evdev->pressed;
+    return 0;
+}
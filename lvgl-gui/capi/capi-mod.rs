@@ -37,3 +37,24 @@ macro_rules! impl_static_imgbin {
 
 // export static img asset
 include!("../assets/@img-assets.rs");
+
+// png assets converted at build time (cf: capi/build-assets.rs)
+include!(concat!(env!("OUT_DIR"), "/img-generated.rs"));
+
+// named registry over every compiled asset, hand written and build time generated ones
+impl AssetPixmap {
+    pub fn by_name(name: &str) -> Option<&'static LvglImgDsc> {
+        ASSET_REGISTRY
+            .iter()
+            .find(|(label, _)| *label == name)
+            .map(|(_, asset)| asset())
+    }
+
+    pub fn iter() -> impl Iterator<Item = (&'static str, &'static LvglImgDsc)> {
+        ASSET_REGISTRY.iter().map(|(name, asset)| (*name, asset()))
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        ASSET_REGISTRY.iter().map(|(name, _)| *name)
+    }
+}
@@ -0,0 +1,643 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// Binding json config schema, checked before any lvgl object is created.
+// Widget entries should be kept in sync with panel-config.rs structures.
+
+use crate::prelude::*;
+use serde_json::{Map, Value};
+use std::fmt;
+
+pub enum ConfigSchema {
+    Any,
+    Bool,
+    // integer within bounds of the rust field it is deserialized into
+    Range { min: i64, max: i64 },
+    // integer or float
+    Number,
+    Str,
+    // {red,green,blue}, "#RRGGBB" or palette name
+    Color,
+    Enum { values: &'static [&'static str] },
+    Array { item: &'static ConfigSchema },
+    // free keys, ex: pixmap states
+    Map { item: &'static ConfigSchema },
+    Object { fields: &'static [SchemaField] },
+    // theme is either a theme uid or an inline theme object
+    StrOrObject { object: &'static ConfigSchema },
+    // panel entry, fields depend on 'type'
+    Widget,
+}
+
+pub struct SchemaField {
+    pub name: &'static str,
+    pub schema: &'static ConfigSchema,
+    pub required: bool,
+    // json literal inserted when key is missing
+    pub default: Option<&'static str>,
+}
+
+macro_rules! field {
+    ($name:expr, $schema:expr) => {
+        SchemaField {
+            name: $name,
+            schema: $schema,
+            required: false,
+            default: None,
+        }
+    };
+    ($name:expr, $schema:expr, required) => {
+        SchemaField {
+            name: $name,
+            schema: $schema,
+            required: true,
+            default: None,
+        }
+    };
+    ($name:expr, $schema:expr, default = $default:expr) => {
+        SchemaField {
+            name: $name,
+            schema: $schema,
+            required: false,
+            default: Some($default),
+        }
+    };
+}
+
+static I16: ConfigSchema = ConfigSchema::Range {
+    min: i16::MIN as i64,
+    max: i16::MAX as i64,
+};
+
+static I32: ConfigSchema = ConfigSchema::Range {
+    min: i32::MIN as i64,
+    max: i32::MAX as i64,
+};
+
+static U8: ConfigSchema = ConfigSchema::Range {
+    min: 0,
+    max: u8::MAX as i64,
+};
+
+static U16: ConfigSchema = ConfigSchema::Range {
+    min: 0,
+    max: u16::MAX as i64,
+};
+
+static U32: ConfigSchema = ConfigSchema::Range {
+    min: 0,
+    max: u32::MAX as i64,
+};
+
+// display resolution is handled as i16 by lvgl
+static RESOLUTION: ConfigSchema = ConfigSchema::Range {
+    min: 1,
+    max: i16::MAX as i64,
+};
+
+static FONT: ConfigSchema = ConfigSchema::Object {
+    fields: &[field!("family", &ConfigSchema::Str), field!("size", &U16)],
+};
+
+static THEME: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("uid", &ConfigSchema::Str),
+        field!("dark", &ConfigSchema::Bool),
+        field!("primary", &ConfigSchema::Color, required),
+        field!("secondary", &ConfigSchema::Color, required),
+        field!("font", &FONT),
+    ],
+};
+
+static CALIBRATION: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("swap_xy", &ConfigSchema::Bool),
+        field!("invert_x", &ConfigSchema::Bool),
+        field!("invert_y", &ConfigSchema::Bool),
+        field!(
+            "matrix",
+            &ConfigSchema::Array {
+                item: &ConfigSchema::Number
+            }
+        ),
+    ],
+};
+
+static EVDEV: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("device", &ConfigSchema::Str, required),
+        field!(
+            "type",
+            &ConfigSchema::Enum {
+                values: &["touch", "keyboard"]
+            },
+            default = "\"touch\""
+        ),
+        field!("calibration", &CALIBRATION),
+    ],
+};
+
+static DRM: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("device", &ConfigSchema::Str, default = "\"/dev/dri/card0\""),
+        field!("connector", &ConfigSchema::Str),
+        field!("mode", &ConfigSchema::Str),
+    ],
+};
+
+static DISPLAY: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("x_res", &RESOLUTION, required),
+        field!("y_res", &RESOLUTION, required),
+        field!("ratio", &U32, default = "1"),
+        field!(
+            "driver",
+            &ConfigSchema::Enum {
+                values: &["native", "fbdev", "gtk", "drm", "kms", "offscreen"]
+            }
+        ),
+        field!("drm", &DRM),
+        field!("evdev", &ConfigSchema::Array { item: &EVDEV }),
+    ],
+};
+
+static WIDGETS: ConfigSchema = ConfigSchema::Array {
+    item: &ConfigSchema::Widget,
+};
+
+static SCREEN: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("uid", &ConfigSchema::Str, required),
+        field!("background", &ConfigSchema::Color),
+        field!("panel", &WIDGETS),
+    ],
+};
+
+// binder level keys (uid, api, path, ...) are part of every binding config
+pub static BINDING_SCHEMA: ConfigSchema = ConfigSchema::Object {
+    fields: &[
+        field!("uid", &ConfigSchema::Str),
+        field!("api", &ConfigSchema::Str),
+        field!("info", &ConfigSchema::Str),
+        field!("path", &ConfigSchema::Str),
+        field!("permission", &ConfigSchema::Str),
+        field!("test", &ConfigSchema::Any),
+        field!("verbose", &ConfigSchema::Any),
+        field!("logo", &ConfigSchema::Str),
+        field!("display", &DISPLAY, required),
+        field!(
+            "theme",
+            &ConfigSchema::StrOrObject { object: &THEME },
+            default = "\"day\""
+        ),
+        field!("themes", &ConfigSchema::Array { item: &THEME }),
+        field!("screens", &ConfigSchema::Array { item: &SCREEN }),
+        field!("panel", &WIDGETS),
+        field!("snapshot_dir", &ConfigSchema::Str, default = "\"/tmp\""),
+        field!("image_cache_kb", &U32),
+        field!("journal", &ConfigSchema::Str),
+    ],
+};
+
+static COMMON: [SchemaField; 11] = [
+    field!("uid", &ConfigSchema::Str, required),
+    field!("info", &ConfigSchema::Str),
+    field!("x", &I16),
+    field!("y", &I16),
+    field!("width", &I16),
+    field!("height", &I16),
+    field!("color", &ConfigSchema::Color),
+    field!("background", &ConfigSchema::Color),
+    field!(
+        "border",
+        &ConfigSchema::Object {
+            fields: &[
+                field!("width", &I16, required),
+                field!("color", &ConfigSchema::Color, required),
+            ]
+        }
+    ),
+    field!(
+        "title",
+        &ConfigSchema::Object {
+            fields: &[
+                field!("text", &ConfigSchema::Str, required),
+                field!("x", &I16),
+                field!("y", &I16),
+                field!("font", &ConfigSchema::Str),
+            ]
+        }
+    ),
+    field!("disable", &ConfigSchema::Bool),
+];
+
+static STATES: ConfigSchema = ConfigSchema::Map {
+    item: &ConfigSchema::Str,
+};
+
+// widget 'type' with its specific keys, common keys are accepted by every type
+static WIDGET_TYPES: [(&str, &[SchemaField]); 13] = [
+    (
+        "label",
+        &[
+            field!("font", &ConfigSchema::Str),
+            field!("value", &ConfigSchema::Str),
+        ],
+    ),
+    (
+        "button",
+        &[
+            field!("font", &ConfigSchema::Str),
+            field!("value", &ConfigSchema::Str),
+        ],
+    ),
+    (
+        "bar",
+        &[
+            field!("min", &I32),
+            field!("max", &I32),
+            field!(
+                "gradient",
+                &ConfigSchema::Object {
+                    fields: &[
+                        field!("vertical", &ConfigSchema::Bool),
+                        field!("start", &ConfigSchema::Color, required),
+                        field!("end", &ConfigSchema::Color, required),
+                    ]
+                }
+            ),
+            field!("value", &I32),
+        ],
+    ),
+    (
+        "meter",
+        &[
+            field!("needle_width", &I32, required),
+            field!("needle_length", &I32, required),
+            field!("needle_color", &ConfigSchema::Color, required),
+            field!(
+                "tic",
+                &ConfigSchema::Object {
+                    fields: &[
+                        field!("width", &I32, required),
+                        field!("length", &I32, required),
+                        field!("count", &I32, required),
+                        field!("major_length", &I32, required),
+                        field!("major_nth", &I32, required),
+                        field!("color", &ConfigSchema::Color, required),
+                        field!("major_color", &ConfigSchema::Color, required),
+                    ]
+                }
+            ),
+            field!(
+                "zones",
+                &ConfigSchema::Array {
+                    item: &ConfigSchema::Object {
+                        fields: &[
+                            field!("start", &I32, required),
+                            field!("end", &I32, required),
+                            field!("width", &I32, required),
+                            field!("color", &ConfigSchema::Color, required),
+                        ]
+                    }
+                }
+            ),
+            field!("value", &I32),
+        ],
+    ),
+    (
+        "led",
+        &[
+            field!("brightness", &U8),
+            field!("value", &ConfigSchema::Bool),
+        ],
+    ),
+    ("switch", &[field!("value", &ConfigSchema::Bool)]),
+    (
+        "arc",
+        &[
+            field!("start", &I32),
+            field!("end", &I32),
+            field!("value", &I32),
+        ],
+    ),
+    (
+        "qrcode",
+        &[
+            field!("dark", &ConfigSchema::Color, required),
+            field!("light", &ConfigSchema::Color, required),
+            field!("size", &I16, required),
+            field!("value", &ConfigSchema::Str),
+        ],
+    ),
+    (
+        "line",
+        &[
+            field!("line_width", &I16),
+            field!("rounded", &ConfigSchema::Bool),
+            field!(
+                "points",
+                &ConfigSchema::Array {
+                    item: &ConfigSchema::Object {
+                        fields: &[field!("x", &I16, required), field!("y", &I16, required)]
+                    }
+                },
+                required
+            ),
+        ],
+    ),
+    ("textarea", &[field!("value", &ConfigSchema::Str)]),
+    (
+        "pixmap",
+        &[
+            field!("icon", &ConfigSchema::Str, required),
+            field!("states", &STATES),
+        ],
+    ),
+    (
+        "pixbutton",
+        &[
+            field!("value", &ConfigSchema::Str),
+            field!("states", &STATES),
+        ],
+    ),
+    ("area", &[field!("widgets", &WIDGETS)]),
+];
+
+// one schema violation with json path to offending value, ex: display.ration
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, format: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(format, "config '{}' {}", self.path, self.message)
+    }
+}
+
+impl From<ConfigError> for DisplayError {
+    fn from(error: ConfigError) -> Self {
+        DisplayError::Config(error.to_string())
+    }
+}
+
+// edit distance used to suggest closest known key or value
+fn distance(first: &str, second: &str) -> usize {
+    let second: Vec<char> = second.chars().collect();
+    let mut previous: Vec<usize> = (0..=second.len()).collect();
+    for (index, char1) in first.chars().enumerate() {
+        let mut current = vec![index + 1];
+        for (jndex, char2) in second.iter().enumerate() {
+            let cost = if char1 == *char2 { 0 } else { 1 };
+            let value = (previous[jndex] + cost)
+                .min(previous[jndex + 1] + 1)
+                .min(current[jndex] + 1);
+            current.push(value);
+        }
+        previous = current;
+    }
+    previous[second.len()]
+}
+
+pub fn did_you_mean<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let limit = (name.len() / 3).max(2);
+    candidates
+        .map(|candidate| (distance(name.as_str(), candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+fn unknown_message(kind: &str, name: &str, candidates: &[&'static str]) -> String {
+    match did_you_mean(name, candidates.iter().copied()) {
+        Some(hint) => format!("unknown {} '{}', did you mean '{}'?", kind, name, hint),
+        None => format!(
+            "unknown {} '{}' (expect: {})",
+            kind,
+            name,
+            candidates.join(", ")
+        ),
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+struct Validator {
+    errors: Vec<ConfigError>,
+}
+
+impl Validator {
+    fn error(&mut self, path: &str, message: String) {
+        self.errors.push(ConfigError {
+            path: path.to_string(),
+            message,
+        });
+    }
+
+    fn expect(&mut self, path: &str, expected: &str, value: &Value) {
+        self.error(
+            path,
+            format!("expect {}, got {}", expected, type_name(value)),
+        );
+    }
+
+    fn check(&mut self, path: &str, schema: &ConfigSchema, value: &mut Value) {
+        match schema {
+            ConfigSchema::Any => {}
+            ConfigSchema::Bool => {
+                if !value.is_boolean() {
+                    self.expect(path, "boolean", value);
+                }
+            }
+            ConfigSchema::Range { min, max } => match value.as_i64() {
+                Some(number) if number >= *min && number <= *max => {}
+                _ if value.is_i64() || value.is_u64() => {
+                    self.error(path, format!("{} out of range [{}, {}]", value, min, max))
+                }
+                _ => self.expect(path, "integer", value),
+            },
+            ConfigSchema::Number => {
+                if !value.is_number() {
+                    self.expect(path, "number", value);
+                }
+            }
+            ConfigSchema::Str => {
+                if !value.is_string() {
+                    self.expect(path, "string", value);
+                }
+            }
+            ConfigSchema::Color => match value {
+                Value::String(text) => {
+                    if let Err(error) = text.parse::<ColorConfig>() {
+                        self.error(path, error);
+                    }
+                }
+                Value::Object(_) => {
+                    static RGB: [SchemaField; 3] = [
+                        field!("red", &U8, required),
+                        field!("green", &U8, required),
+                        field!("blue", &U8, required),
+                    ];
+                    self.check_object(path, &[&RGB], value);
+                }
+                _ => self.expect(
+                    path,
+                    "color {red,green,blue}, '#RRGGBB' or palette name",
+                    value,
+                ),
+            },
+            ConfigSchema::Enum { values } => match value.as_str() {
+                Some(text) => {
+                    if !values.contains(&text) {
+                        self.error(path, unknown_message("value", text, values));
+                    }
+                }
+                None => self.expect(path, "string", value),
+            },
+            ConfigSchema::Array { item } => match value.as_array_mut() {
+                Some(items) => {
+                    for (index, value) in items.iter_mut().enumerate() {
+                        self.check(format!("{}[{}]", path, index).as_str(), item, value);
+                    }
+                }
+                None => self.expect(path, "array", value),
+            },
+            ConfigSchema::Map { item } => match value.as_object_mut() {
+                Some(map) => {
+                    for (key, value) in map.iter_mut() {
+                        self.check(format!("{}.{}", path, key).as_str(), item, value);
+                    }
+                }
+                None => self.expect(path, "object", value),
+            },
+            ConfigSchema::Object { fields } => self.check_object(path, &[fields], value),
+            ConfigSchema::StrOrObject { object } => {
+                if !value.is_string() {
+                    self.check(path, object, value);
+                }
+            }
+            ConfigSchema::Widget => self.check_widget(path, value),
+        }
+    }
+
+    fn check_widget(&mut self, path: &str, value: &mut Value) {
+        let kind = match value.get("type") {
+            Some(Value::String(kind)) => kind.clone(),
+            Some(kind) => return self.expect(format!("{}.type", path).as_str(), "string", kind),
+            None => return self.error(path, "missing mandatory key 'type'".to_string()),
+        };
+        match WIDGET_TYPES.iter().find(|(name, _)| *name == kind) {
+            Some((_, fields)) => self.check_object(path, &[&COMMON, *fields], value),
+            None => {
+                let types: Vec<&'static str> = WIDGET_TYPES.iter().map(|(name, _)| *name).collect();
+                self.error(
+                    format!("{}.type", path).as_str(),
+                    unknown_message("widget type", kind.as_str(), &types),
+                )
+            }
+        }
+    }
+
+    // fields may come from several tables, ex: widget common and type specific keys
+    fn check_object(&mut self, path: &str, tables: &[&[SchemaField]], value: &mut Value) {
+        let object: &mut Map<String, Value> = match value.as_object_mut() {
+            Some(object) => object,
+            None => return self.expect(path, "object", value),
+        };
+        let fields = || tables.iter().flat_map(|table| table.iter());
+        let prefix = |name: &str| match path {
+            "" => name.to_string(),
+            _ => format!("{}.{}", path, name),
+        };
+
+        for key in object.keys() {
+            // widget 'type' is checked by caller
+            if key == "type" && tables.len() > 1 {
+                continue;
+            }
+            if !fields().any(|field| field.name == key) {
+                let names: Vec<&'static str> = fields().map(|field| field.name).collect();
+                let message = unknown_message("key", key, &names);
+                self.error(prefix(key).as_str(), message);
+            }
+        }
+
+        for field in fields() {
+            match object.get_mut(field.name) {
+                Some(value) => self.check(prefix(field.name).as_str(), field.schema, value),
+                None => {
+                    if field.required {
+                        self.error(
+                            prefix(field.name).as_str(),
+                            "missing mandatory key".to_string(),
+                        );
+                    } else if let Some(default) = field.default {
+                        if let Ok(value) = serde_json::from_str(default) {
+                            object.insert(field.name.to_string(), value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+// check json value against schema, missing optional keys receive their default value
+pub fn config_validate(schema: &ConfigSchema, config: &mut Value) -> Result<(), Vec<ConfigError>> {
+    let mut validator = Validator { errors: Vec::new() };
+    validator.check("", schema, config);
+    if validator.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(validator.errors)
+    }
+}
+
+// validate full binding config and return it with defaults applied
+pub fn binding_config_from_json(json: &str) -> Result<Value, Vec<ConfigError>> {
+    let mut config: Value = match serde_json::from_str(json) {
+        Ok(config) => config,
+        Err(error) => {
+            return Err(vec![ConfigError {
+                path: String::new(),
+                message: format!("invalid json: {}", error),
+            }])
+        }
+    };
+    config_validate(&BINDING_SCHEMA, &mut config)?;
+    Ok(config)
+}
+
+pub fn config_errors_to_string(errors: &[ConfigError]) -> String {
+    errors
+        .iter()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+        .join("\n")
+}
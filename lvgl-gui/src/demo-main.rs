@@ -0,0 +1,441 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// Standalone panel simulator: build a panel from a binding json config without afb-binder,
+// then apply widget commands read from stdin or from a script file.
+//   lvgl-gui [--config binding.json] [--script commands.txt] [--driver native|drm|offscreen] [--exit]
+//            [--journal session.jsonl] [--replay session.jsonl] [--speed 1.0]
+
+use lvgl_gui::prelude::*;
+use serde_json::Value;
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "usage: lvgl-gui [--config binding.json] [--script commands.txt] [--driver native|drm|offscreen] [--exit]
+                [--journal session.jsonl] [--replay session.jsonl] [--speed 1.0]
+
+  --journal  record commands and widget events
+  --replay   replay a binding or simulator journal (panel config comes from journal unless --config is given)
+  --speed    replay speed factor, 0 replays without delay
+
+commands (one per line, '#' starts a comment):
+  set <uid> <value>            update widget value (text, number, on/off, pixmap state)
+  show|hide|enable|disable <uid>
+  [{\"uid\":..,\"action\":..,\"value\":..}]   json batch, applied within one refresh cycle
+  get [uid]                    print widget state (every widget when uid is missing)
+  panel                        print panel widgets description
+  screen <uid> [transition] [time-ms]
+  theme <uid>
+  image <uid> <path>
+  input {\"action\":\"click\",\"uid\":..}   inject click, press, release, drag or key
+  snapshot <file.png> [uid]
+  sleep <ms>
+  quit";
+
+struct DemoArgs {
+    config: Option<String>,
+    script: Option<String>,
+    driver: Option<String>,
+    journal: Option<String>,
+    replay: Option<String>,
+    speed: f64,
+    exit: bool,
+}
+
+fn parse_args() -> Result<DemoArgs, String> {
+    let mut args = DemoArgs {
+        config: None,
+        script: None,
+        driver: None,
+        journal: None,
+        replay: None,
+        speed: 1.0,
+        exit: false,
+    };
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        match arg.as_str() {
+            "-c" | "--config" => args.config = argv.next(),
+            "-s" | "--script" => args.script = argv.next(),
+            "-d" | "--driver" => args.driver = argv.next(),
+            "-j" | "--journal" => args.journal = argv.next(),
+            "-r" | "--replay" => args.replay = argv.next(),
+            "--speed" => {
+                args.speed = match argv.next().map(|value| value.parse::<f64>()) {
+                    Some(Ok(speed)) if speed >= 0.0 => speed,
+                    _ => return Err(format!("invalid --speed value\n{}", USAGE)),
+                }
+            }
+            "-x" | "--exit" => args.exit = true,
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ => return Err(format!("unknown argument:{}\n{}", arg, USAGE)),
+        }
+    }
+    Ok(args)
+}
+
+// accept either a full afb-binder config (first binding with a 'display') or a single binding object
+fn load_config(path: &str) -> Result<Value, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {}", path, error))?;
+    let json: Value =
+        serde_json::from_str(&text).map_err(|error| format!("{}: {}", path, error))?;
+    let mut config = match json.get("binding").and_then(|value| value.as_array()) {
+        Some(bindings) => match bindings
+            .iter()
+            .find(|binding| binding.get("display").is_some())
+        {
+            Some(binding) => binding.clone(),
+            None => return Err(format!("{}: no binding with 'display' config", path)),
+        },
+        None => json,
+    };
+
+    // same schema check as display binding, defaults are applied in place
+    if let Err(errors) = config_validate(&BINDING_SCHEMA, &mut config) {
+        return Err(format!("{}:\n{}", path, config_errors_to_string(&errors)));
+    }
+    Ok(config)
+}
+
+fn get_u32(json: &Value, key: &str, default: u32) -> u32 {
+    json.get(key)
+        .and_then(|value| value.as_u64())
+        .map(|value| value as u32)
+        .unwrap_or(default)
+}
+
+fn load_panel(config: &Value) -> Result<PanelConfig, String> {
+    if let Some(jscreens) = config.get("screens") {
+        Ok(PanelConfig::Screens(screens_from_json(
+            jscreens.to_string().as_str(),
+        )?))
+    } else if let Some(jpanel) = config.get("panel") {
+        Ok(PanelConfig::Panel(panel_from_json(
+            jpanel.to_string().as_str(),
+        )?))
+    } else {
+        Ok(PanelConfig::Demo)
+    }
+}
+
+// print widget events on stdout, designers see what a client would receive
+struct DemoEvtCtx {}
+impl LvglHandler for DemoEvtCtx {
+    fn callback(&self, widget: &LvglWidget, uid: &'static str, event: &LvglEvent) {
+        println!("event:{}", WidgetEvent::new(widget, uid, event).to_json());
+    }
+}
+
+struct Demo {
+    display: &'static DisplayHandle,
+    themes: Vec<ThemeConfig>,
+}
+
+// unquoted values are text, json literals (numbers, true/false, "quoted text") are parsed
+fn parse_value(text: &str) -> Value {
+    match serde_json::from_str::<Value>(text) {
+        Ok(value) => value,
+        Err(_) => Value::String(text.to_string()),
+    }
+}
+
+impl Demo {
+    fn batch(&self, operations: Vec<BatchOperation>) -> Result<(), String> {
+        let display = self.display;
+        display.journal_verb("batch", &operations);
+        let status = unsafe { display.exec(move || batch_apply(display, &operations)) }?;
+        match status {
+            Ok(_) => Ok(()),
+            Err(results) => Err(batch_results_to_json(&results)),
+        }
+    }
+
+    fn operation(&self, uid: &str, action: &str, value: Value) -> Result<(), String> {
+        let operation = serde_json::json!({"uid": uid, "action": action, "value": value});
+        self.batch(batch_from_json(format!("[{}]", operation).as_str())?)
+    }
+
+    // return false when demo should stop
+    fn command(&self, line: &str) -> Result<bool, String> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(true);
+        }
+        if line.starts_with('[') {
+            self.batch(batch_from_json(line)?)?;
+            return Ok(true);
+        }
+        if line.starts_with('{') {
+            self.batch(batch_from_json(format!("[{}]", line).as_str())?)?;
+            return Ok(true);
+        }
+
+        let (command, args) = match line.split_once(char::is_whitespace) {
+            Some((command, args)) => (command, args.trim()),
+            None => (line, ""),
+        };
+        let command = command.to_lowercase();
+        let mut words = args.split_whitespace();
+        let display = self.display;
+        match command.as_str() {
+            "set" => match args.split_once(char::is_whitespace) {
+                Some((uid, value)) => self.operation(uid, "set", parse_value(value.trim()))?,
+                None => return Err("usage: set <uid> <value>".to_string()),
+            },
+            "show" | "hide" | "enable" | "disable" => match words.next() {
+                Some(uid) => self.operation(uid, command.as_str(), Value::Null)?,
+                None => return Err(format!("usage: {} <uid>", command)),
+            },
+            "get" => {
+                let uid = words.next().map(|uid| uid.to_string());
+                let states = unsafe {
+                    display.exec(move || match uid {
+                        Some(uid) => match display.get_state(uid.as_str()) {
+                            Some(state) => Ok(vec![state.to_json()]),
+                            None => Err(DisplayError::UnknownUid(uid).to_string()),
+                        },
+                        None => Ok(display
+                            .get_panel()
+                            .into_iter()
+                            .map(|widget| WidgetState::new(widget).to_json())
+                            .collect()),
+                    })
+                }??;
+                for state in states {
+                    println!("{}", state);
+                }
+            }
+            "panel" => {
+                let infos = unsafe {
+                    display.exec(move || {
+                        display
+                            .get_panel()
+                            .into_iter()
+                            .map(|widget| WidgetInfo::new(widget).to_json())
+                            .collect::<Vec<String>>()
+                    })
+                }?;
+                for info in infos {
+                    println!("{}", info);
+                }
+            }
+            "screen" => {
+                let uid = match words.next() {
+                    Some(uid) => uid.to_string(),
+                    None => return Err("usage: screen <uid> [transition] [time-ms]".to_string()),
+                };
+                let transition = match words.next() {
+                    Some(name) => {
+                        serde_json::from_value::<ScreenTransition>(Value::String(name.to_string()))
+                            .map_err(|error| format!("invalid transition:{} {}", name, error))?
+                    }
+                    None => ScreenTransition::None,
+                };
+                let time = match words.next() {
+                    Some(time) => time
+                        .parse::<u32>()
+                        .map_err(|error| format!("invalid time:{} {}", time, error))?,
+                    None => 0,
+                };
+                display.journal_verb(
+                    "screen",
+                    &serde_json::json!({"uid": uid, "transition": transition, "time": time}),
+                );
+                unsafe {
+                    display.exec(move || display.set_screen(uid.as_str(), transition, time))
+                }??;
+            }
+            "theme" => {
+                let uid = words.next().unwrap_or_default();
+                let theme = match self.themes.iter().find(|theme| theme.uid == uid) {
+                    Some(theme) => theme.clone(),
+                    None => return Err(format!("unknown theme uid:{}", uid)),
+                };
+                display.journal_verb("theme", &serde_json::json!({ "uid": uid }));
+                unsafe { display.exec(move || display.apply_theme(&theme)) }??;
+            }
+            "image" => {
+                let (uid, path) = match (words.next(), words.next()) {
+                    (Some(uid), Some(path)) => (uid.to_string(), path.to_string()),
+                    _ => return Err("usage: image <uid> <path>".to_string()),
+                };
+                display.journal_verb("image", &serde_json::json!({"uid": uid, "path": path}));
+                let info = unsafe {
+                    display.exec(move || {
+                        display.load_image(uid.as_str(), &ImageSource::File(path), None)
+                    })
+                }??;
+                println!("image:{} {}x{}", info.key, info.width, info.height);
+            }
+            "input" => {
+                let request = input_from_json(args)?;
+                display.journal_verb("input", &request);
+                if let Some(point) = input_inject(display, &request)? {
+                    println!("input:{:?} x:{} y:{}", request.action, point.x, point.y);
+                }
+            }
+            "snapshot" => {
+                let path = match words.next() {
+                    Some(path) => path.to_string(),
+                    None => return Err("usage: snapshot <file.png> [uid]".to_string()),
+                };
+                let uid = words.next().map(|uid| uid.to_string());
+                let png =
+                    unsafe { display.exec(move || display.snapshot(uid.as_deref())?.to_png()) }??;
+                fs::write(&path, png).map_err(|error| format!("{}: {}", path, error))?;
+            }
+            "sleep" => match words.next().and_then(|value| value.parse::<u64>().ok()) {
+                Some(delay) => thread::sleep(Duration::from_millis(delay)),
+                None => return Err("usage: sleep <ms>".to_string()),
+            },
+            "quit" | "exit" => return Ok(false),
+            "help" => println!("{}", USAGE),
+            _ => return Err(format!("unknown command:{} (help)", command)),
+        }
+        Ok(true)
+    }
+
+    // errors are reported with their line number and do not stop the script
+    fn run(&self, reader: &mut dyn BufRead, name: &str) -> bool {
+        for (index, line) in reader.lines().enumerate() {
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => {
+                    eprintln!("{}: {}", name, error);
+                    return true;
+                }
+            };
+            match self.command(line.as_str()) {
+                Ok(true) => {}
+                Ok(false) => return false,
+                Err(error) => eprintln!("{}:{} {}", name, index + 1, error),
+            }
+        }
+        true
+    }
+}
+
+fn start(args: DemoArgs) -> Result<(), String> {
+    let replay = match &args.replay {
+        Some(path) => Some(JournalReplay::open(path)?),
+        None => None,
+    };
+    let config = match (&args.config, &replay) {
+        (Some(path), _) => load_config(path)?,
+        (None, Some(replay)) => replay.get_config().clone(),
+        (None, None) => Value::Null,
+    };
+
+    let jdisplay = config.get("display").cloned().unwrap_or(Value::Null);
+    let driver = match args
+        .driver
+        .as_deref()
+        .or(jdisplay.get("driver").and_then(|value| value.as_str()))
+    {
+        Some(name) => name.parse::<DisplayDriver>()?,
+        None => DisplayDriver::default(),
+    };
+    let (x_res, y_res, ratio) = (
+        get_u32(&jdisplay, "x_res", 1024) as i16,
+        get_u32(&jdisplay, "y_res", 600) as i16,
+        get_u32(&jdisplay, "ratio", 1),
+    );
+    let mut display = match (driver, jdisplay.get("drm")) {
+        (DisplayDriver::Drm, Some(jdrm)) => {
+            let config = drm_from_json(jdrm.to_string().as_str())?;
+            DisplayHandle::create_with_drm(x_res, y_res, ratio, &config)?
+        }
+        _ => DisplayHandle::create_with_driver(x_res, y_res, ratio, driver)?,
+    };
+    if let Some(mode) = display.get_drm_mode() {
+        println!(
+            "drm connector:{} mode:{}x{}@{}",
+            mode.connector, mode.width, mode.height, mode.refresh
+        );
+    }
+
+    let (themes, theme) = themes_from_config(&config)?;
+    if let Some(config) = themes.iter().find(|config| config.uid == theme) {
+        display.set_theme_config(config)?;
+    }
+    if let Some(value) = config
+        .get("image_cache_kb")
+        .and_then(|value| value.as_u64())
+    {
+        display.set_image_budget(value as usize * 1024);
+    }
+
+    if let Some(path) = &args.journal {
+        display.set_journal(DisplayJournal::create(path, &config)?);
+    }
+    display.set_callback(Box::new(DemoEvtCtx {}));
+    display.draw_config(&load_panel(&config)?)?;
+    display.finalize()?;
+    let demo = Demo {
+        display: Box::leak(Box::new(display)),
+        themes,
+    };
+
+    // replayed records are reported with their time, failures do not stop replay
+    if let Some(replay) = &replay {
+        replay.run(
+            demo.display,
+            &demo.themes,
+            args.speed,
+            &mut |record, status| {
+                if let Err(error) = status {
+                    eprintln!(
+                        "replay:{}ms {} {}",
+                        record.get_time(),
+                        record.to_json(),
+                        error
+                    );
+                }
+            },
+        );
+    }
+
+    let running = match (&args.script, &replay) {
+        (Some(path), _) => {
+            let file = fs::File::open(path).map_err(|error| format!("{}: {}", path, error))?;
+            demo.run(&mut BufReader::new(file), path)
+        }
+        (None, Some(_)) => true,
+        (None, None) => demo.run(&mut io::stdin().lock(), "stdin"),
+    };
+
+    // keep native/drm display alive after script or replay so designers can play with the panel
+    let scripted = args.script.is_some() || replay.is_some();
+    if running && !args.exit && scripted && driver != DisplayDriver::Offscreen {
+        loop {
+            thread::sleep(Duration::from_secs(3600));
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}", error);
+            process::exit(1);
+        }
+    };
+    if let Err(error) = start(args) {
+        eprintln!("lvgl-gui: {}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,306 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// evdev touchscreen/keyboard input (capi-map.c), independent of fbdev or GTK display driver.
+// Touch raw axis are normalized to 0..1, optionally swapped/inverted, then mapped through
+// a libinput like matrix: x' = m0*x + m1*y + m2, y' = m3*x + m4*y + m5 (x',y' scaled to display).
+
+use crate::capi::cglue;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::io;
+
+// should match capi-map.c CAPI_EVDEV_xxx
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum EvdevKind {
+    #[default]
+    Touch,
+    Keyboard,
+}
+
+const EVDEV_IDENTITY: [f32; 6] = [1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+
+fn evdev_identity() -> [f32; 6] {
+    EVDEV_IDENTITY
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct EvdevCalibration {
+    #[serde(default)]
+    pub swap_xy: bool,
+    #[serde(default)]
+    pub invert_x: bool,
+    #[serde(default)]
+    pub invert_y: bool,
+    #[serde(default = "evdev_identity")]
+    pub matrix: [f32; 6],
+}
+
+impl Default for EvdevCalibration {
+    fn default() -> Self {
+        EvdevCalibration {
+            swap_xy: false,
+            invert_x: false,
+            invert_y: false,
+            matrix: EVDEV_IDENTITY,
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct EvdevConfig {
+    pub device: String,
+    #[serde(rename = "type", default)]
+    pub kind: EvdevKind,
+    #[serde(default)]
+    pub calibration: EvdevCalibration,
+}
+
+// display 'evdev' config is an array of devices
+pub fn evdev_from_json(json: &str) -> Result<Vec<EvdevConfig>, String> {
+    match serde_json::from_str::<Vec<EvdevConfig>>(json) {
+        Ok(configs) => Ok(configs),
+        Err(error) => Err(format!("invalid evdev config: {}", error)),
+    }
+}
+
+// touched point: raw is normalized device position (0..1), screen is expected position in pixels
+#[derive(Deserialize, Serialize, Debug, Clone, Copy)]
+pub struct CalibrationPoint {
+    pub raw: [f32; 2],
+    pub screen: [f32; 2],
+}
+
+// 3 points calibration, result matrix holds swap/inversion, returned calibration does not swap or invert raw axis
+pub fn calibration_from_points(
+    points: &[CalibrationPoint],
+    x_res: i16,
+    y_res: i16,
+) -> Result<EvdevCalibration, String> {
+    if points.len() != 3 {
+        return Err(format!("calibration expect 3 points, got {}", points.len()));
+    }
+    let [p0, p1, p2] = [points[0].raw, points[1].raw, points[2].raw];
+    let det = p0[0] * (p1[1] - p2[1]) - p0[1] * (p1[0] - p2[0]) + (p1[0] * p2[1] - p2[0] * p1[1]);
+    if det.abs() < 1e-6 {
+        return Err("calibration points should not be aligned".to_string());
+    }
+
+    // solve [x y 1] * [a b c] = target for each screen axis (Cramer's rule)
+    let solve = |target: [f32; 3]| -> [f32; 3] {
+        let a = (target[0] * (p1[1] - p2[1]) - p0[1] * (target[1] - target[2])
+            + (target[1] * p2[1] - target[2] * p1[1]))
+            / det;
+        let b = (p0[0] * (target[1] - target[2]) - target[0] * (p1[0] - p2[0])
+            + (p1[0] * target[2] - p2[0] * target[1]))
+            / det;
+        let c = (p0[0] * (p1[1] * target[2] - p2[1] * target[1])
+            - p0[1] * (p1[0] * target[2] - p2[0] * target[1])
+            + target[0] * (p1[0] * p2[1] - p2[0] * p1[1]))
+            / det;
+        [a, b, c]
+    };
+    let scale = |value: f32, resolution: i16| value / (resolution as f32 - 1.0).max(1.0);
+    let x = solve([
+        scale(points[0].screen[0], x_res),
+        scale(points[1].screen[0], x_res),
+        scale(points[2].screen[0], x_res),
+    ]);
+    let y = solve([
+        scale(points[0].screen[1], y_res),
+        scale(points[1].screen[1], y_res),
+        scale(points[2].screen[1], y_res),
+    ]);
+    Ok(EvdevCalibration {
+        matrix: [x[0], x[1], x[2], y[0], y[1], y[2]],
+        ..EvdevCalibration::default()
+    })
+}
+
+// runtime calibration change, missing fields keep their current value
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct CalibrateRequest {
+    pub device: Option<String>,
+    pub swap_xy: Option<bool>,
+    pub invert_x: Option<bool>,
+    pub invert_y: Option<bool>,
+    pub matrix: Option<[f32; 6]>,
+    pub points: Option<Vec<CalibrationPoint>>,
+}
+
+pub fn calibrate_from_json(json: &str) -> Result<CalibrateRequest, String> {
+    match serde_json::from_str::<CalibrateRequest>(json) {
+        Ok(request) => Ok(request),
+        Err(error) => Err(format!("invalid calibration: {}", error)),
+    }
+}
+
+pub(crate) struct EvdevDevice {
+    device: String,
+    kind: EvdevKind,
+    index: i32,
+    calibration: EvdevCalibration,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EvdevRaw {
+    pub x: f32,
+    pub y: f32,
+    pub pressed: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct EvdevInfo {
+    pub device: String,
+    #[serde(rename = "type")]
+    pub kind: EvdevKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<EvdevCalibration>,
+    // last touch before calibration, used to pick calibration points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub raw: Option<EvdevRaw>,
+}
+
+impl EvdevDevice {
+    fn get_info(&self) -> EvdevInfo {
+        let (calibration, raw) = match self.kind {
+            EvdevKind::Keyboard => (None, None),
+            EvdevKind::Touch => {
+                let (mut x, mut y, mut pressed) = (0.0, 0.0, false);
+                unsafe { cglue::capi_evdev_get_raw(self.index, &mut x, &mut y, &mut pressed) };
+                (
+                    Some(self.calibration.clone()),
+                    Some(EvdevRaw { x, y, pressed }),
+                )
+            }
+        };
+        EvdevInfo {
+            device: self.device.clone(),
+            kind: self.kind,
+            calibration,
+            raw,
+        }
+    }
+
+    // push calibration to capi-map.c, device keeps its previous calibration on failure
+    fn apply(&self, calibration: &EvdevCalibration) -> Result<(), String> {
+        let status = unsafe {
+            cglue::capi_evdev_calibrate(
+                self.index,
+                calibration.swap_xy,
+                calibration.invert_x,
+                calibration.invert_y,
+                calibration.matrix.as_ptr(),
+            )
+        };
+        if status < 0 {
+            return Err(format!(
+                "evdev:{} does not support calibration",
+                self.device
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl DisplayHandle {
+    // open evdev device as lvgl input, should be called before lvgl loop starts or from lvgl thread
+    pub fn add_evdev(&self, config: &EvdevConfig) -> Result<(), DisplayError> {
+        let path = match CString::new(config.device.as_str()) {
+            Ok(path) => path,
+            Err(error) => return Err(DisplayError::Config(error.to_string())),
+        };
+        let kind = match config.kind {
+            EvdevKind::Touch => 0,
+            EvdevKind::Keyboard => 1,
+        };
+        let index = unsafe { cglue::capi_evdev_open(path.as_ptr(), kind) };
+        if index < 0 {
+            return Err(DisplayError::Config(format!(
+                "fail to open evdev:{} {}",
+                config.device,
+                io::Error::from_raw_os_error(-index)
+            )));
+        }
+        let device = EvdevDevice {
+            device: config.device.clone(),
+            kind: config.kind,
+            index,
+            calibration: config.calibration.clone(),
+        };
+        if device.kind == EvdevKind::Touch {
+            device.apply(&device.calibration)?;
+        }
+        self.lock_evdevs().push(device);
+        Ok(())
+    }
+
+    pub fn get_evdevs(&self) -> Vec<EvdevInfo> {
+        self.lock_evdevs()
+            .iter()
+            .map(|device| device.get_info())
+            .collect()
+    }
+
+    // update touch calibration (first touch device when none is given), should be called from lvgl thread
+    pub fn calibrate(&self, request: &CalibrateRequest) -> Result<EvdevInfo, DisplayError> {
+        let mut evdevs = self.lock_evdevs();
+        let device = match evdevs.iter_mut().find(|device| {
+            device.kind == EvdevKind::Touch
+                && match &request.device {
+                    Some(name) => name == &device.device,
+                    None => true,
+                }
+        }) {
+            Some(device) => device,
+            None => {
+                return Err(DisplayError::Config(format!(
+                    "no evdev touch device:{}",
+                    request.device.as_deref().unwrap_or("*")
+                )))
+            }
+        };
+
+        let mut calibration = match &request.points {
+            Some(points) => {
+                let (x_res, y_res) = self.get_resolution();
+                calibration_from_points(points, x_res, y_res)?
+            }
+            None => device.calibration.clone(),
+        };
+        if let Some(value) = request.swap_xy {
+            calibration.swap_xy = value;
+        }
+        if let Some(value) = request.invert_x {
+            calibration.invert_x = value;
+        }
+        if let Some(value) = request.invert_y {
+            calibration.invert_y = value;
+        }
+        if let Some(value) = request.matrix {
+            calibration.matrix = value;
+        }
+        device.apply(&calibration)?;
+        device.calibration = calibration;
+        Ok(device.get_info())
+    }
+}
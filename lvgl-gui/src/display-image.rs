@@ -0,0 +1,248 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::capi::cglue;
+use image::io::{Limits, Reader};
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::ffi::c_void;
+use std::hash::{Hash, Hasher};
+use std::io::Cursor;
+
+// default lvgl descriptors memory budget (8MB)
+pub const IMAGE_CACHE_BUDGET: usize = 8 * 1024 * 1024;
+
+// lvgl v8 image header stores width/height on 11 bits
+const IMAGE_SIZE_MAX: u32 = 2047;
+
+// runtime image either read from filesystem or uploaded as raw png/jpeg/bmp blob
+pub enum ImageSource {
+    File(String),
+    Data(Vec<u8>),
+}
+
+impl ImageSource {
+    // file images are cached by path, blobs by content hash
+    pub fn get_key(&self) -> String {
+        match self {
+            ImageSource::File(path) => format!("file:{}", path),
+            ImageSource::Data(data) => {
+                let mut hasher = DefaultHasher::new();
+                data.hash(&mut hasher);
+                format!("data:{:016x}", hasher.finish())
+            }
+        }
+    }
+
+    // decoding is pure rust and may run outside of lvgl thread. Image should fit within cache budget,
+    // oversized sources are rejected before decoding and decoder allocation is capped.
+    pub fn decode(&self, budget: usize) -> Result<DecodedImage, String> {
+        let mut limits = Limits::default();
+        limits.max_image_width = Some(IMAGE_SIZE_MAX);
+        limits.max_image_height = Some(IMAGE_SIZE_MAX);
+        limits.max_alloc = Some(budget as u64);
+
+        let image = match self {
+            ImageSource::File(path) => {
+                let length = std::fs::metadata(path)
+                    .map_err(|error| format!("fail to load {}: {}", path, error))?
+                    .len();
+                if length > budget as u64 {
+                    return Err(format!(
+                        "image {} size:{} exceeds cache budget:{}",
+                        path, length, budget
+                    ));
+                }
+                let mut reader = Reader::open(path)
+                    .map_err(|error| format!("fail to load {}: {}", path, error))?;
+                reader.limits(limits);
+                reader
+                    .decode()
+                    .map_err(|error| format!("fail to load {}: {}", path, error))?
+            }
+            ImageSource::Data(data) => {
+                if data.len() > budget {
+                    return Err(format!(
+                        "image blob size:{} exceeds cache budget:{}",
+                        data.len(),
+                        budget
+                    ));
+                }
+                let mut reader = Reader::new(Cursor::new(data.as_slice()))
+                    .with_guessed_format()
+                    .map_err(|error| format!("fail to decode image blob: {}", error))?;
+                reader.limits(limits);
+                reader
+                    .decode()
+                    .map_err(|error| format!("fail to decode image blob: {}", error))?
+            }
+        };
+
+        // decoder may output rgb, check rgba copy also fits
+        let size = image.width() as usize * image.height() as usize * 4;
+        if size > budget {
+            return Err(format!(
+                "image {}x{} exceeds cache budget:{}",
+                image.width(),
+                image.height(),
+                budget
+            ));
+        }
+        let rgba = image.to_rgba8();
+        Ok(DecodedImage {
+            width: rgba.width(),
+            height: rgba.height(),
+            rgba: rgba.into_raw(),
+        })
+    }
+}
+
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ImageInfo {
+    pub key: String,
+    pub width: u32,
+    pub height: u32,
+    pub cached: bool,
+}
+
+struct ImageEntry {
+    key: String,
+    dsc: *mut c_void,
+    size: usize,
+    width: u32,
+    height: u32,
+    // widgets currently displaying this descriptor, entry cannot be evicted while in use
+    users: Vec<String>,
+}
+
+// lvgl descriptors in LRU order (most recent last), only accessed from lvgl thread
+pub struct ImageCache {
+    budget: usize,
+    used: usize,
+    entries: Vec<ImageEntry>,
+}
+
+impl ImageCache {
+    pub fn new(budget: usize) -> Self {
+        ImageCache {
+            budget,
+            used: 0,
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.evict();
+    }
+
+    pub fn get_budget(&self) -> usize {
+        self.budget
+    }
+
+    pub fn get_used(&self) -> usize {
+        self.used
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.iter().any(|entry| entry.key == key)
+    }
+
+    // return lvgl descriptor for uid, image is converted only on cache miss
+    pub(crate) fn acquire(
+        &mut self,
+        uid: &str,
+        source: &ImageSource,
+        decoded: Option<DecodedImage>,
+    ) -> Result<(*mut c_void, ImageInfo), String> {
+        let key = source.get_key();
+
+        let (index, cached) = match self.entries.iter().position(|entry| entry.key == key) {
+            Some(index) => (index, true),
+            None => {
+                let image = match decoded {
+                    Some(image) => image,
+                    None => source.decode(self.budget)?,
+                };
+                let mut size = 0;
+                let dsc = unsafe {
+                    cglue::capi_img_dsc_create(
+                        image.rgba.as_ptr(),
+                        image.width,
+                        image.height,
+                        &mut size,
+                    )
+                };
+                if dsc.is_null() {
+                    return Err(format!("fail to allocate image descriptor for {}", key));
+                }
+                self.used += size;
+                self.entries.push(ImageEntry {
+                    key: key.clone(),
+                    dsc,
+                    size,
+                    width: image.width,
+                    height: image.height,
+                    users: Vec::new(),
+                });
+                (self.entries.len() - 1, false)
+            }
+        };
+
+        // previous image stays in use until the new one is ready, then entry becomes most recently used
+        self.release(uid);
+        let mut entry = self.entries.remove(index);
+        entry.users.push(uid.to_string());
+        let dsc = entry.dsc;
+        let info = ImageInfo {
+            key,
+            width: entry.width,
+            height: entry.height,
+            cached,
+        };
+        self.entries.push(entry);
+        self.evict();
+        Ok((dsc, info))
+    }
+
+    // widget does not display its cached image anymore
+    pub(crate) fn release(&mut self, uid: &str) {
+        for entry in &mut self.entries {
+            entry.users.retain(|user| user != uid);
+        }
+    }
+
+    // free least recently used descriptors not displayed by any widget until budget is respected
+    fn evict(&mut self) {
+        let mut index = 0;
+        while self.used > self.budget && index < self.entries.len() {
+            if self.entries[index].users.is_empty() {
+                let entry = self.entries.remove(index);
+                unsafe { cglue::capi_img_dsc_free(entry.dsc) };
+                self.used -= entry.size;
+            } else {
+                index += 1;
+            }
+        }
+    }
+}
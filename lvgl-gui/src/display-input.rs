@@ -0,0 +1,253 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// Synthetic input: samples go through virtual lvgl pointer/keypad devices (capi-map.c),
+// widgets receive the same events as from the touch screen.
+
+use crate::capi::cglue;
+use crate::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+
+// default press time for click and key, default drag duration
+const INPUT_CLICK_MS: u32 = 50;
+const INPUT_DRAG_MS: u32 = 300;
+// drag moves, a whole drag should fit within virtual pointer queue (cf: CAPI_INPUT_QUEUE)
+const INPUT_DRAG_STEPS: u32 = 100;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InputAction {
+    Click,
+    Press,
+    Release,
+    Drag,
+    Key,
+}
+
+// target is either a widget uid (its center) or absolute x,y coordinates
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct InputRequest {
+    pub action: InputAction,
+    pub uid: Option<String>,
+    pub x: Option<i16>,
+    pub y: Option<i16>,
+    // drag destination
+    pub to_uid: Option<String>,
+    pub to_x: Option<i16>,
+    pub to_y: Option<i16>,
+    // control key name (enter, esc, left, ...)
+    pub key: Option<String>,
+    // text typed char by char
+    pub text: Option<String>,
+    // press time for click/key, move time for drag (ms)
+    pub duration: Option<u32>,
+}
+
+pub fn input_from_json(json: &str) -> Result<InputRequest, String> {
+    match serde_json::from_str::<InputRequest>(json) {
+        Ok(request) => Ok(request),
+        Err(error) => Err(format!("invalid input: {}", error)),
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+pub struct InputPoint {
+    pub x: i16,
+    pub y: i16,
+}
+
+// sample state is held for given ms before next sample is read (cf: capi_input_pop)
+#[derive(Clone, Copy)]
+enum InputSample {
+    Pointer(InputPoint, bool, u32),
+    Key(u32, bool, u32),
+}
+
+impl DisplayHandle {
+    // widget center in screen coordinates, should be called from lvgl thread
+    pub fn get_widget_center(&self, uid: &str) -> Result<InputPoint, DisplayError> {
+        match self.find_widget(uid) {
+            Some(widget) => {
+                let geometry = get_widget_geometry(widget);
+                Ok(InputPoint {
+                    x: geometry.x + geometry.width / 2,
+                    y: geometry.y + geometry.height / 2,
+                })
+            }
+            None => Err(DisplayError::UnknownUid(uid.to_string())),
+        }
+    }
+
+    // give keypad focus to widget, should be called from lvgl thread
+    pub fn focus_widget(&self, uid: &str) -> Result<(), DisplayError> {
+        let widget = match self.find_widget(uid) {
+            Some(widget) => widget,
+            None => return Err(DisplayError::UnknownUid(uid.to_string())),
+        };
+        let handle = get_widget_handle(widget)?;
+        unsafe {
+            if cglue::capi_input_init() < 0 || cglue::capi_input_focus(handle) < 0 {
+                return Err(DisplayError::Config(
+                    "fail to create virtual input".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // queue samples to virtual devices, should be called from lvgl thread. Timing is done by lvgl
+    // indev read timer, a sequence is queued as a whole or rejected.
+    fn input_push(&self, samples: &[InputSample]) -> Result<(), String> {
+        if unsafe { cglue::capi_input_init() } < 0 {
+            return Err("fail to create virtual input".to_string());
+        }
+        let keys = samples
+            .iter()
+            .filter(|sample| matches!(sample, InputSample::Key(..)))
+            .count();
+        let pointers = samples.len() - keys;
+        unsafe {
+            if keys > cglue::capi_input_free(true) as usize
+                || pointers > cglue::capi_input_free(false) as usize
+            {
+                return Err("virtual input queue full".to_string());
+            }
+        }
+        for sample in samples {
+            match sample {
+                InputSample::Pointer(point, pressed, hold) => unsafe {
+                    cglue::capi_input_pointer(point.x, point.y, *pressed, *hold)
+                },
+                InputSample::Key(key, pressed, hold) => unsafe {
+                    cglue::capi_input_key(*key, *pressed, *hold)
+                },
+            };
+        }
+        Ok(())
+    }
+}
+
+fn key_code(key: &str) -> Result<u32, String> {
+    let name = match CString::new(key.to_lowercase()) {
+        Ok(name) => name,
+        Err(error) => return Err(error.to_string()),
+    };
+    match unsafe { cglue::capi_input_key_code(name.as_ptr()) } {
+        0 => Err(format!(
+            "unknown key:{} (up|down|right|left|esc|del|backspace|enter|next|prev|home|end)",
+            key
+        )),
+        code => Ok(code),
+    }
+}
+
+fn get_point(
+    display: &'static DisplayHandle,
+    uid: &Option<String>,
+    x: Option<i16>,
+    y: Option<i16>,
+) -> Result<Option<InputPoint>, String> {
+    match (uid, x, y) {
+        (Some(uid), _, _) => {
+            let uid = uid.clone();
+            Ok(Some(unsafe {
+                display.exec(move || display.get_widget_center(uid.as_str()))
+            }??))
+        }
+        (None, Some(x), Some(y)) => Ok(Some(InputPoint { x, y })),
+        (None, None, None) => Ok(None),
+        _ => Err("input expect both x and y coordinates".to_string()),
+    }
+}
+
+// inject request samples, timed sequence is played by lvgl while caller returns as soon as it is queued
+// return pointer position (none for key input)
+pub fn input_inject(
+    display: &'static DisplayHandle,
+    request: &InputRequest,
+) -> Result<Option<InputPoint>, String> {
+    let point = get_point(display, &request.uid, request.x, request.y)?;
+    let mut samples = Vec::new();
+    match request.action {
+        InputAction::Key => {
+            let codes = match (&request.key, &request.text) {
+                (Some(key), None) => vec![key_code(key.as_str())?],
+                (None, Some(text)) if !text.is_empty() => {
+                    text.chars().map(|char| char as u32).collect()
+                }
+                _ => return Err("key input expect either a 'key' name or a 'text'".to_string()),
+            };
+            if let Some(uid) = request.uid.clone() {
+                unsafe { display.exec(move || display.focus_widget(uid.as_str())) }??;
+            }
+            for code in codes {
+                let hold = request.duration.unwrap_or(INPUT_CLICK_MS);
+                samples.push(InputSample::Key(code, true, hold));
+                samples.push(InputSample::Key(code, false, 0));
+            }
+        }
+        action => {
+            let Some(point) = point else {
+                return Err("pointer input expect a uid or x,y coordinates".to_string());
+            };
+            match action {
+                InputAction::Press => samples.push(InputSample::Pointer(point, true, 0)),
+                InputAction::Release => samples.push(InputSample::Pointer(point, false, 0)),
+                InputAction::Click => {
+                    let hold = request.duration.unwrap_or(INPUT_CLICK_MS);
+                    samples.push(InputSample::Pointer(point, true, hold));
+                    samples.push(InputSample::Pointer(point, false, 0));
+                }
+                // drag
+                _ => {
+                    let Some(to) = get_point(display, &request.to_uid, request.to_x, request.to_y)?
+                    else {
+                        return Err("drag expect a to_uid or to_x,to_y destination".to_string());
+                    };
+                    // one move every 10ms, lvgl reads input devices every LV_INDEV_DEF_READ_PERIOD
+                    let duration = request.duration.unwrap_or(INPUT_DRAG_MS);
+                    let steps = (duration / 10).clamp(1, INPUT_DRAG_STEPS);
+                    let hold = duration / steps;
+                    let steps = steps as i32;
+                    samples.push(InputSample::Pointer(point, true, hold));
+                    for step in 1..=steps {
+                        samples.push(InputSample::Pointer(
+                            InputPoint {
+                                x: (point.x as i32 + (to.x as i32 - point.x as i32) * step / steps)
+                                    as i16,
+                                y: (point.y as i32 + (to.y as i32 - point.y as i32) * step / steps)
+                                    as i16,
+                            },
+                            true,
+                            hold,
+                        ));
+                    }
+                    samples.push(InputSample::Pointer(to, false, 0));
+                }
+            }
+        }
+    }
+
+    unsafe { display.exec(move || display.input_push(&samples)) }??;
+
+    match request.action {
+        InputAction::Key => Ok(None),
+        _ => Ok(point),
+    }
+}
@@ -0,0 +1,332 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+// Session journal: one json record per line, incoming verb calls and widget events with their time
+// (ms since journal start). First line holds binding config, replay rebuilds the same panel from it.
+
+use crate::capi::cglue;
+use crate::prelude::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lvgl::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::{BufRead, BufReader, LineWriter, Write};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum JournalRecord {
+    Start {
+        epoch: u64,
+        config: Value,
+    },
+    Verb {
+        time: u64,
+        verb: String,
+        args: Value,
+    },
+    Event {
+        time: u64,
+        uid: String,
+        event: String,
+        value: Value,
+    },
+}
+
+impl JournalRecord {
+    pub fn get_time(&self) -> u64 {
+        match self {
+            JournalRecord::Start { .. } => 0,
+            JournalRecord::Verb { time, .. } => *time,
+            JournalRecord::Event { time, .. } => *time,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+pub struct DisplayJournal {
+    writer: Mutex<LineWriter<File>>,
+    start: Instant,
+}
+
+impl DisplayJournal {
+    // journal lives as long as the display, config is written as first record
+    pub fn create(path: &str, config: &Value) -> Result<&'static Self, String> {
+        let file = match File::create(path) {
+            Ok(file) => file,
+            Err(error) => return Err(format!("fail to create journal {}: {}", path, error)),
+        };
+        let journal = Box::leak(Box::new(DisplayJournal {
+            writer: Mutex::new(LineWriter::new(file)),
+            start: Instant::now(),
+        }));
+        let epoch = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as u64,
+            Err(_) => 0,
+        };
+        journal.write(&JournalRecord::Start {
+            epoch,
+            config: config.clone(),
+        });
+        Ok(journal)
+    }
+
+    fn get_time(&self) -> u64 {
+        self.start.elapsed().as_millis() as u64
+    }
+
+    // journal should never break display, write errors are ignored
+    fn write(&self, record: &JournalRecord) {
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{}", record.to_json());
+        }
+    }
+
+    pub fn record_verb<T: Serialize>(&self, verb: &str, args: &T) {
+        self.write(&JournalRecord::Verb {
+            time: self.get_time(),
+            verb: verb.to_string(),
+            args: serde_json::to_value(args).unwrap_or_default(),
+        });
+    }
+
+    pub fn record_event(&self, widget: &LvglWidget, uid: &str, event: &LvglEvent) {
+        self.write(&JournalRecord::Event {
+            time: self.get_time(),
+            uid: uid.to_string(),
+            event: get_event_name(event).to_string(),
+            value: serde_json::to_value(get_widget_value(widget)).unwrap_or_default(),
+        });
+    }
+}
+
+// record widget event, then forward it to display handler
+struct JournalHandler {
+    journal: &'static DisplayJournal,
+    handler: Box<dyn LvglHandler>,
+}
+
+impl LvglHandler for JournalHandler {
+    fn callback(&self, widget: &LvglWidget, uid: &'static str, event: &LvglEvent) {
+        self.journal.record_event(widget, uid, event);
+        self.handler.callback(widget, uid, event);
+    }
+}
+
+pub(crate) fn journal_handler(
+    journal: &'static DisplayJournal,
+    handler: Box<dyn LvglHandler>,
+) -> Box<dyn LvglHandler> {
+    Box::new(JournalHandler { journal, handler })
+}
+
+// user input is restored before event is sent, handlers then read the same value as during recorded session
+fn replay_event(
+    display: &DisplayHandle,
+    uid: &str,
+    event: &str,
+    value: &Value,
+) -> Result<(), String> {
+    let widget = match display.find_widget(uid) {
+        Some(widget) => widget,
+        None => return Err(DisplayError::UnknownUid(uid.to_string()).to_string()),
+    };
+    // same setters as widget verbs, widgets without readable value record null
+    if !value.is_null() {
+        let operation = BatchOperation {
+            uid: uid.to_string(),
+            action: BatchAction::Set,
+            value: value.clone(),
+        };
+        BatchUpdate::new(display, &operation)?.apply();
+    }
+
+    let name = match CString::new(event) {
+        Ok(name) => name,
+        Err(error) => return Err(error.to_string()),
+    };
+    let handle = get_widget_handle(widget)?;
+    if unsafe { cglue::capi_event_send(handle, name.as_ptr()) } < 0 {
+        return Err(format!("widget uid:{} unsupported event:{}", uid, event));
+    }
+    Ok(())
+}
+
+fn replay_verb(
+    display: &DisplayHandle,
+    themes: &[ThemeConfig],
+    verb: &str,
+    args: &Value,
+) -> Result<(), String> {
+    let uid = args.get("uid").and_then(|value| value.as_str());
+    match verb {
+        // read only or client side verbs do not change display
+        "get" | "panel" | "snapshot" | "event" => {}
+        // widget events triggered by injected input are journaled and replayed on their own
+        "input" => {}
+        // touch calibration is specific to target evdev hardware
+        "calibrate" => {}
+        "batch" => {
+            let operations = batch_from_json(args.to_string().as_str())?;
+            if let Err(results) = batch_apply(display, &operations) {
+                return Err(batch_results_to_json(&results));
+            }
+        }
+        "screen" => {
+            if let Some(uid) = uid {
+                let transition = match args.get("transition") {
+                    Some(value) => serde_json::from_value(value.clone()).unwrap_or_default(),
+                    None => ScreenTransition::None,
+                };
+                let time = args
+                    .get("time")
+                    .and_then(|value| value.as_u64())
+                    .unwrap_or(0);
+                display.set_screen(uid, transition, time as u32)?;
+            }
+        }
+        "theme" => {
+            if let Some(uid) = uid {
+                match themes.iter().find(|theme| theme.uid == uid) {
+                    Some(theme) => display.apply_theme(theme)?,
+                    None => return Err(format!("unknown theme uid:{}", uid)),
+                }
+            }
+        }
+        "create" => {
+            let config = create_from_json(args.to_string().as_str())?;
+            display.create_widget(&config.widget, config.screen.as_deref())?;
+        }
+        "delete" => match uid {
+            Some(uid) => {
+                display.delete_widget(uid)?;
+            }
+            None => return Err("delete record without uid".to_string()),
+        },
+        "image" => {
+            let uid = uid.unwrap_or_default();
+            let source = match (args.get("path"), args.get("data")) {
+                (Some(Value::String(path)), _) => ImageSource::File(path.clone()),
+                (_, Some(Value::String(data))) => match STANDARD.decode(data.as_str()) {
+                    Ok(blob) => ImageSource::Data(blob),
+                    Err(error) => return Err(format!("invalid base64 data: {}", error)),
+                },
+                _ => return Err("image record without path or data".to_string()),
+            };
+            display.load_image(uid, &source, None)?;
+        }
+        // widget verb: text, number, pixmap state or {"action":"ON|OFF"}
+        widget => {
+            let value = match args.get("action") {
+                Some(action) => action.clone(),
+                None => args.clone(),
+            };
+            let operation = BatchOperation {
+                uid: widget.to_string(),
+                action: BatchAction::Set,
+                value,
+            };
+            if let Err(results) = batch_apply(display, &[operation]) {
+                return Err(batch_results_to_json(&results));
+            }
+        }
+    }
+    Ok(())
+}
+
+// apply one record, should be called from lvgl thread (cf: exec)
+pub fn journal_apply(
+    display: &DisplayHandle,
+    themes: &[ThemeConfig],
+    record: &JournalRecord,
+) -> Result<(), String> {
+    match record {
+        JournalRecord::Start { .. } => Ok(()),
+        JournalRecord::Verb { verb, args, .. } => replay_verb(display, themes, verb, args),
+        JournalRecord::Event {
+            uid, event, value, ..
+        } => replay_event(display, uid, event, value),
+    }
+}
+
+pub struct JournalReplay {
+    config: Value,
+    records: Vec<JournalRecord>,
+}
+
+impl JournalReplay {
+    pub fn open(path: &str) -> Result<Self, String> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(error) => return Err(format!("fail to open journal {}: {}", path, error)),
+        };
+        let mut config = Value::Null;
+        let mut records = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|error| format!("{}: {}", path, error))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<JournalRecord>(line.as_str()) {
+                Ok(JournalRecord::Start { config: value, .. }) => config = value,
+                Ok(record) => records.push(record),
+                Err(error) => {
+                    return Err(format!("{}:{} invalid record: {}", path, index + 1, error))
+                }
+            }
+        }
+        Ok(JournalReplay { config, records })
+    }
+
+    // binding config recorded with the session
+    pub fn get_config(&self) -> &Value {
+        &self.config
+    }
+
+    pub fn get_records(&self) -> &Vec<JournalRecord> {
+        &self.records
+    }
+
+    // replay records with their recorded delays divided by speed (0: no delay), report is called after each record
+    pub fn run(
+        &self,
+        display: &'static DisplayHandle,
+        themes: &[ThemeConfig],
+        speed: f64,
+        report: &mut dyn FnMut(&JournalRecord, Result<(), String>),
+    ) {
+        let mut previous = 0;
+        for record in &self.records {
+            let delay = record.get_time().saturating_sub(previous);
+            previous = record.get_time();
+            if speed > 0.0 && delay > 0 {
+                thread::sleep(Duration::from_secs_f64(delay as f64 / 1000.0 / speed));
+            }
+
+            let (themes, job) = (themes.to_vec(), record.clone());
+            let status = unsafe { display.exec(move || journal_apply(display, &themes, &job)) };
+            report(record, status.and_then(|status| status));
+        }
+    }
+}
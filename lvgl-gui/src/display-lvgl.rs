@@ -15,28 +15,401 @@
  * limitations under the License.
  */
 
+use crate::capi::cglue;
 use crate::prelude::*;
 use lvgl::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashMap;
+use std::ffi::{c_char, CStr, CString};
+use std::fmt;
+use std::io;
+use std::ptr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DisplayError {
+    UnknownUid(String),
+    WrongType {
+        uid: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+    DuplicateUid(String),
+    Config(String),
+}
+
+impl fmt::Display for DisplayError {
+    fn fmt(&self, format: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DisplayError::UnknownUid(uid) => write!(format, "no widget uid:{} found in panel", uid),
+            DisplayError::WrongType {
+                uid,
+                expected,
+                found,
+            } => write!(format, "widget uid:{} is a {} not a {}", uid, found, expected),
+            DisplayError::DuplicateUid(uid) => write!(format, "duplicated widget uid:{}", uid),
+            DisplayError::Config(error) => write!(format, "{}", error),
+        }
+    }
+}
+
+impl From<String> for DisplayError {
+    fn from(error: String) -> Self {
+        DisplayError::Config(error)
+    }
+}
+
+impl From<DisplayError> for String {
+    fn from(error: DisplayError) -> Self {
+        error.to_string()
+    }
+}
+
+// widget pools are sorted by uid, they may change at runtime through create/delete
+type WidgetPool = RwLock<Vec<&'static LvglWidget>>;
+
+fn read_pool(pool: &WidgetPool) -> RwLockReadGuard<'_, Vec<&'static LvglWidget>> {
+    pool.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write_pool(pool: &WidgetPool) -> RwLockWriteGuard<'_, Vec<&'static LvglWidget>> {
+    pool.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn search_pool(pool: &[&'static LvglWidget], uid: &str) -> Result<usize, usize> {
+    pool.binary_search_by(|widget| widget.get_uid().cmp(uid))
+}
+
+// transition code should match capi-map.c CAPI_SCREEN_xxx
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum ScreenTransition {
+    #[default]
+    None,
+    Fade,
+    SlideLeft,
+    SlideRight,
+}
+
+impl ScreenTransition {
+    fn to_capi(&self) -> i32 {
+        match self {
+            ScreenTransition::None => 0,
+            ScreenTransition::Fade => 1,
+            ScreenTransition::SlideLeft => 2,
+            ScreenTransition::SlideRight => 3,
+        }
+    }
+}
+
+// native driver is fbdev or GTK depending on lvgl-rclib build, drm uses KMS dumb buffers, offscreen render in RAM
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisplayDriver {
+    Native,
+    Drm,
+    Offscreen,
+}
+
+impl Default for DisplayDriver {
+    fn default() -> Self {
+        if cfg!(feature = "offscreen") {
+            DisplayDriver::Offscreen
+        } else {
+            DisplayDriver::Native
+        }
+    }
+}
+
+impl FromStr for DisplayDriver {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "native" | "fbdev" | "gtk" => Ok(DisplayDriver::Native),
+            "drm" | "kms" => Ok(DisplayDriver::Drm),
+            "offscreen" => Ok(DisplayDriver::Offscreen),
+            _ => Err(format!("unsupported display driver:{} (native|drm|offscreen)", name)),
+        }
+    }
+}
+
+fn drm_default_device() -> String {
+    "/dev/dri/card0".to_string()
+}
+
+// DRM/KMS output (cargo feature 'drm'), connector is a kernel name (HDMI-A-1, DSI-1, Virtual-1),
+// mode is "WxH" or "WxH@R". Missing connector selects first connected one, missing mode uses display resolution.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct DrmConfig {
+    #[serde(default = "drm_default_device")]
+    pub device: String,
+    pub connector: Option<String>,
+    pub mode: Option<String>,
+}
+
+impl Default for DrmConfig {
+    fn default() -> Self {
+        DrmConfig {
+            device: drm_default_device(),
+            connector: None,
+            mode: None,
+        }
+    }
+}
+
+pub fn drm_from_json(json: &str) -> Result<DrmConfig, String> {
+    match serde_json::from_str::<DrmConfig>(json) {
+        Ok(config) => Ok(config),
+        Err(error) => Err(format!("invalid drm config: {}", error)),
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DrmMode {
+    pub connector: String,
+    pub width: u16,
+    pub height: u16,
+    pub refresh: u32,
+}
+
+// RGBA8888 copy of rendered display
+pub struct DisplayFrame {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
+
+impl DisplayFrame {
+    pub fn to_png(&self) -> Result<Vec<u8>, String> {
+        let mut buffer = Vec::new();
+        let mut encoder = png::Encoder::new(&mut buffer, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let status = encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.rgba));
+        match status {
+            Ok(()) => Ok(buffer),
+            Err(error) => Err(format!("fail to encode png: {}", error)),
+        }
+    }
+}
+
+pub struct DisplayScreen {
+    uid: &'static str,
+    container: &'static LvglWidget,
+    panel: WidgetPool,
+}
+
+impl DisplayScreen {
+    pub fn get_uid(&self) -> &'static str {
+        self.uid
+    }
+
+    pub fn get_panel(&self) -> Vec<&'static LvglWidget> {
+        read_pool(&self.panel).clone()
+    }
+
+    pub fn find_widget(&self, uid: &str) -> Option<&'static LvglWidget> {
+        let panel = read_pool(&self.panel);
+        match search_pool(&panel, uid) {
+            Ok(index) => Some(panel[index]),
+            Err(_) => None,
+        }
+    }
+}
+
+// area are not indexed within panel, runtime created ones are kept to delete their whole subtree
+struct CreatedArea {
+    uid: &'static str,
+    area: &'static LvglWidget,
+    uids: Vec<&'static str>,
+}
 
 pub struct DisplayHandle {
     handle: LvglHandle,
-    panel: Vec<&'static LvglWidget>,
+    x_res: i16,
+    y_res: i16,
+    driver: DisplayDriver,
+    screens: Vec<DisplayScreen>,
+    // written from lvgl thread, read from any afb thread
+    current: AtomicUsize,
+    panel: WidgetPool,
+    areas: RwLock<Vec<CreatedArea>>,
+    states: RwLock<HashMap<String, HashMap<String, String>>>,
+    images: Mutex<ImageCache>,
+    evdevs: Mutex<Vec<EvdevDevice>>,
+    journal: Option<&'static DisplayJournal>,
+    queue: &'static DisplayQueue,
     ctrlbox: Option<*mut dyn LvglHandler>,
 }
 
 impl DisplayHandle {
     pub fn create(x_res: i16, y_res: i16, ratio: u32) -> Self {
+        DisplayHandle::new(x_res, y_res, ratio, DisplayDriver::Native)
+    }
+
+    // native fbdev/GTK init is only done for native driver (cf: capi_native_set_skip)
+    fn new(x_res: i16, y_res: i16, ratio: u32, driver: DisplayDriver) -> Self {
+        unsafe { cglue::capi_native_set_skip(driver != DisplayDriver::Native) };
         let handle = LvglHandle::new(x_res, y_res, ratio);
 
         let display = DisplayHandle {
             handle,
-            panel: Vec::new(),
+            x_res,
+            y_res,
+            driver,
+            screens: Vec::new(),
+            current: AtomicUsize::new(0),
+            panel: RwLock::new(Vec::new()),
+            areas: RwLock::new(Vec::new()),
+            states: RwLock::new(HashMap::new()),
+            images: Mutex::new(ImageCache::new(IMAGE_CACHE_BUDGET)),
+            evdevs: Mutex::new(Vec::new()),
+            journal: None,
+            queue: DisplayQueue::new(),
             ctrlbox: None,
         };
         display
     }
 
+    pub fn create_with_driver(
+        x_res: i16,
+        y_res: i16,
+        ratio: u32,
+        driver: DisplayDriver,
+    ) -> Result<Self, String> {
+        match driver {
+            DisplayDriver::Native => Ok(DisplayHandle::create(x_res, y_res, ratio)),
+            DisplayDriver::Drm => {
+                DisplayHandle::create_with_drm(x_res, y_res, ratio, &DrmConfig::default())
+            }
+            DisplayDriver::Offscreen => {
+                let display = DisplayHandle::new(x_res, y_res, ratio, driver);
+                if unsafe { cglue::capi_offscreen_init() } < 0 {
+                    return Err("fail to allocate offscreen display buffer".to_string());
+                }
+                Ok(display)
+            }
+        }
+    }
+
+    // display flush goes to DRM/KMS connector instead of fbdev/GTK
+    pub fn create_with_drm(
+        x_res: i16,
+        y_res: i16,
+        ratio: u32,
+        config: &DrmConfig,
+    ) -> Result<Self, String> {
+        let to_cstring = |value: &str| CString::new(value).map_err(|error| error.to_string());
+        let device = to_cstring(config.device.as_str())?;
+        let connector = config.connector.as_deref().map(to_cstring).transpose()?;
+        let mode = config.mode.as_deref().map(to_cstring).transpose()?;
+
+        let display = DisplayHandle::new(x_res, y_res, ratio, DisplayDriver::Drm);
+        let status = unsafe {
+            cglue::capi_drm_init(
+                device.as_ptr(),
+                connector
+                    .as_ref()
+                    .map_or(ptr::null(), |value| value.as_ptr()),
+                mode.as_ref().map_or(ptr::null(), |value| value.as_ptr()),
+            )
+        };
+        if status < 0 {
+            return Err(format!(
+                "fail to open drm:{} connector:{} mode:{} {}",
+                config.device,
+                config.connector.as_deref().unwrap_or("*"),
+                config.mode.as_deref().unwrap_or("*"),
+                io::Error::from_raw_os_error(-status)
+            ));
+        }
+        Ok(display)
+    }
+
+    // selected connector and mode when display driver is drm
+    pub fn get_drm_mode(&self) -> Option<DrmMode> {
+        if self.driver != DisplayDriver::Drm {
+            return None;
+        }
+        let mut connector = [0 as c_char; 32];
+        let (mut width, mut height, mut refresh) = (0u16, 0u16, 0u32);
+        let status = unsafe {
+            cglue::capi_drm_get_mode(
+                connector.as_mut_ptr(),
+                connector.len(),
+                &mut width,
+                &mut height,
+                &mut refresh,
+            )
+        };
+        if status < 0 {
+            return None;
+        }
+        let connector = unsafe { CStr::from_ptr(connector.as_ptr()) };
+        Some(DrmMode {
+            connector: connector.to_string_lossy().to_string(),
+            width,
+            height,
+            refresh,
+        })
+    }
+
+    /// Lvgl is not thread safe, any widget access from outside lvgl thread should go through exec.
+    ///
+    /// # Safety
+    /// Same as [`DisplayQueue::exec`]: job should only capture owned `Send` values and `'static`
+    /// display/widget references.
+    pub unsafe fn exec<T, F>(&self, job: F) -> Result<T, String>
+    where
+        F: FnOnce() -> T + 'static,
+        T: Send + 'static,
+    {
+        self.queue.exec(job)
+    }
+
+    pub fn get_driver(&self) -> DisplayDriver {
+        self.driver
+    }
+
+    pub fn get_resolution(&self) -> (i16, i16) {
+        (self.x_res, self.y_res)
+    }
+
+    // force pending rendering, to be used when lvgl main loop is not started (tests)
+    pub fn refresh(&self) -> &Self {
+        unsafe { cglue::capi_refresh_now() };
+        self
+    }
+
+    // rendered frame is only readable from offscreen and drm (displayed buffer) backends
+    pub fn get_frame(&self) -> Option<DisplayFrame> {
+        let (mut width, mut height) = (0i16, 0i16);
+        let mut rgba = vec![0u8; self.x_res as usize * self.y_res as usize * 4];
+        let status = match self.driver {
+            DisplayDriver::Native => return None,
+            DisplayDriver::Drm => unsafe {
+                cglue::capi_drm_read(rgba.as_mut_ptr(), rgba.len(), &mut width, &mut height)
+            },
+            DisplayDriver::Offscreen => unsafe {
+                cglue::capi_offscreen_read(rgba.as_mut_ptr(), rgba.len(), &mut width, &mut height)
+            },
+        };
+        if status < 0 {
+            return None;
+        }
+        Some(DisplayFrame {
+            width: width as u32,
+            height: height as u32,
+            rgba,
+        })
+    }
+
     pub fn set_theme(
         &mut self,
         primary: LvglColor,
@@ -48,32 +421,486 @@ impl DisplayHandle {
         self
     }
 
+    // initial theme from config, should be called before panel is drawn
+    pub fn set_theme_config(&mut self, theme: &ThemeConfig) -> Result<&mut Self, DisplayError> {
+        let font = theme.font.to_font()?;
+        Ok(self.set_theme(
+            theme.primary.to_color(),
+            theme.secondary.to_color(),
+            theme.dark,
+            font,
+        ))
+    }
+
+    // re-style whole panel at runtime, should be called from lvgl thread (cf: exec)
+    pub fn apply_theme(&self, theme: &ThemeConfig) -> Result<(), DisplayError> {
+        theme.font.to_font()?;
+        let (primary, secondary) = (&theme.primary, &theme.secondary);
+        let status = unsafe {
+            cglue::capi_theme_apply(
+                primary.red,
+                primary.green,
+                primary.blue,
+                secondary.red,
+                secondary.green,
+                secondary.blue,
+                theme.dark,
+                theme.font.size,
+            )
+        };
+        if status < 0 {
+            return Err(DisplayError::Config(format!("fail to apply theme:{}", theme.uid)));
+        }
+        Ok(())
+    }
+
     pub fn set_callback(&mut self, ctrlbox: Box<dyn LvglHandler>) -> &mut Self {
+        let ctrlbox = match self.journal {
+            Some(journal) => journal_handler(journal, ctrlbox),
+            None => ctrlbox,
+        };
         self.ctrlbox = Some(Box::leak(ctrlbox));
         self
     }
 
-    pub fn get_panel<'a>(&'a self) -> &'a Vec<&'static LvglWidget> {
-        &self.panel
+    // record verb calls and widget events, should be set before set_callback
+    pub fn set_journal(&mut self, journal: &'static DisplayJournal) -> &mut Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    pub fn get_journal(&self) -> Option<&'static DisplayJournal> {
+        self.journal
+    }
+
+    // noop when no journal is set
+    pub fn journal_verb<T: Serialize>(&self, verb: &str, args: &T) {
+        if let Some(journal) = self.journal {
+            journal.record_verb(verb, args);
+        }
+    }
+
+    // widgets from every screen
+    pub fn get_panel(&self) -> Vec<&'static LvglWidget> {
+        read_pool(&self.panel).clone()
     }
 
     pub fn get_root(&self) -> &'static LvglWidget {
         self.handle.get_root_widget()
     }
 
-    pub fn get_by_uid(&self, uid: &str) -> &'static dyn Any {
-        let widget = match self
-            .panel
-            .binary_search_by(|widget| widget.get_uid().cmp(uid))
+    pub fn get_by_uid(&self, uid: &str) -> Result<&'static dyn Any, DisplayError> {
+        match self.find_widget(uid) {
+            Some(widget) => Ok(widget.as_any()),
+            None => Err(DisplayError::UnknownUid(uid.to_string())),
+        }
+    }
+
+    // typed lookup, ex: display.get_widget::<LvglBar>("Bar-1")
+    pub fn get_widget<T: Any>(&self, uid: &str) -> Result<&'static T, DisplayError> {
+        let widget = match self.find_widget(uid) {
+            Some(widget) => widget,
+            None => return Err(DisplayError::UnknownUid(uid.to_string())),
+        };
+        match widget.as_any().downcast_ref::<T>() {
+            Some(this) => Ok(this),
+            None => Err(DisplayError::WrongType {
+                uid: uid.to_string(),
+                expected: get_type_name::<T>(),
+                found: get_widget_type(widget),
+            }),
+        }
+    }
+
+    pub fn find_widget(&self, uid: &str) -> Option<&'static LvglWidget> {
+        let panel = read_pool(&self.panel);
+        match search_pool(&panel, uid) {
+            Ok(index) => Some(panel[index]),
+            Err(_) => None,
+        }
+    }
+
+    // keep pixmap/pixbutton state maps from config, they are used by set_pixmap
+    fn register_states(&self, config: &WidgetConfig) {
+        let mut states = self.states.write().unwrap_or_else(PoisonError::into_inner);
+        config.for_each(&mut |config| {
+            if let Some(map) = config.get_states() {
+                if !map.is_empty() {
+                    states.insert(config.get_uid().to_string(), map.clone());
+                }
+            }
+        });
+    }
+
+    // state name from widget config map or image name (asset for pixbutton, icon for pixmap)
+    pub fn get_pixmap_image(&self, uid: &str, value: &str) -> String {
+        let states = self.states.read().unwrap_or_else(PoisonError::into_inner);
+        match states.get(uid) {
+            Some(map) => get_state(map, value).to_string(),
+            None => value.to_string(),
+        }
+    }
+
+    // should be called from lvgl thread (cf: exec)
+    pub fn set_pixmap(&self, uid: &str, value: &str) -> Result<(), DisplayError> {
+        let widget = match self.find_widget(uid) {
+            Some(widget) => widget,
+            None => return Err(DisplayError::UnknownUid(uid.to_string())),
+        };
+        let image = self.get_pixmap_image(uid, value);
+        match widget {
+            LvglWidget::PixButton(this) => {
+                this.set_value(get_asset(image.as_str())?);
+            }
+            LvglWidget::Pixmap(this) => {
+                this.set_value(get_icon(image.as_str())?);
+            }
+            _ => {
+                return Err(DisplayError::WrongType {
+                    uid: uid.to_string(),
+                    expected: "pixmap",
+                    found: get_widget_type(widget),
+                })
+            }
+        }
+        self.release_image(uid);
+        Ok(())
+    }
+
+    // lvgl descriptors allocated by load_image are freed (LRU) above this budget
+    pub fn set_image_budget(&self, bytes: usize) -> &Self {
+        self.lock_images().set_budget(bytes);
+        self
+    }
+
+    pub fn get_image_budget(&self) -> usize {
+        self.lock_images().get_budget()
+    }
+
+    pub fn is_image_cached(&self, key: &str) -> bool {
+        self.lock_images().contains(key)
+    }
+
+    pub fn get_image_used(&self) -> usize {
+        self.lock_images().get_used()
+    }
+
+    fn lock_images(&self) -> MutexGuard<'_, ImageCache> {
+        self.images.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn lock_evdevs(&self) -> MutexGuard<'_, Vec<EvdevDevice>> {
+        self.evdevs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    // widget switched back to a compiled asset, its runtime image may now be evicted
+    pub(crate) fn release_image(&self, uid: &str) {
+        self.lock_images().release(uid);
+    }
+
+    // display runtime image on pixmap/pixbutton/image, should be called from lvgl thread (cf: exec)
+    // decoded is optional and allows to decode image outside of lvgl thread on cache miss
+    pub fn load_image(
+        &self,
+        uid: &str,
+        source: &ImageSource,
+        decoded: Option<DecodedImage>,
+    ) -> Result<ImageInfo, DisplayError> {
+        let widget = match self.find_widget(uid) {
+            Some(widget) => widget,
+            None => return Err(DisplayError::UnknownUid(uid.to_string())),
+        };
+        match widget {
+            LvglWidget::PixButton(_) | LvglWidget::Pixmap(_) | LvglWidget::Image(_) => {}
+            _ => {
+                return Err(DisplayError::WrongType {
+                    uid: uid.to_string(),
+                    expected: "pixmap or image",
+                    found: get_widget_type(widget),
+                })
+            }
+        }
+
+        let handle = get_widget_handle(widget)?;
+        let mut images = self.lock_images();
+        let (dsc, info) = images.acquire(uid, source, decoded)?;
+        if unsafe { cglue::capi_img_set_src(handle, dsc) } < 0 {
+            images.release(uid);
+            return Err(DisplayError::Config(format!(
+                "widget uid:{} has no image to update",
+                uid
+            )));
+        }
+        Ok(info)
+    }
+
+    pub fn get_state(&self, uid: &str) -> Option<WidgetState> {
+        self.find_widget(uid).map(WidgetState::new)
+    }
+
+    // capture current rendering, either full screen or a single widget bounding box
+    pub fn snapshot(&self, uid: Option<&str>) -> Result<DisplayFrame, String> {
+        let widget = match uid {
+            Some(uid) => match self.find_widget(uid) {
+                Some(widget) => widget,
+                None => return Err(DisplayError::UnknownUid(uid.to_string()).to_string()),
+            },
+            None => self.get_root(),
+        };
+
+        let handle = get_widget_handle(widget)?;
+        let (mut width, mut height) = (0i16, 0i16);
+        let data = unsafe { cglue::capi_snapshot_take(handle, &mut width, &mut height) };
+        if data.is_null() {
+            return Err("fail to take lvgl snapshot".to_string());
+        }
+        let len = width as usize * height as usize * 4;
+        let rgba = unsafe { std::slice::from_raw_parts(data, len).to_vec() };
+        unsafe { cglue::capi_snapshot_free(data) };
+
+        Ok(DisplayFrame {
+            width: width as u32,
+            height: height as u32,
+            rgba,
+        })
+    }
+
+    pub fn get_screens(&self) -> &[DisplayScreen] {
+        &self.screens
+    }
+
+    pub fn get_screen(&self, uid: &str) -> Option<&DisplayScreen> {
+        self.screens.iter().find(|screen| screen.uid == uid)
+    }
+
+    pub fn get_current_screen(&self) -> &'static str {
+        match self.screens.get(self.current.load(Ordering::Relaxed)) {
+            Some(screen) => screen.uid,
+            None => "",
+        }
+    }
+
+    // display requested screen and hide current one, return previous screen uid
+    pub fn set_screen(
+        &self,
+        uid: &str,
+        transition: ScreenTransition,
+        time_ms: u32,
+    ) -> Result<&'static str, String> {
+        let index = match self.screens.iter().position(|screen| screen.uid == uid) {
+            Some(index) => index,
+            None => return Err(format!("unknown screen uid:{}", uid)),
+        };
+        let previous = self.current.load(Ordering::Relaxed);
+        if index != previous {
+            let hidden = get_widget_handle(self.screens[previous].container)?;
+            let shown = get_widget_handle(self.screens[index].container)?;
+            unsafe {
+                cglue::capi_screen_switch(
+                    hidden,
+                    shown,
+                    transition.to_capi(),
+                    time_ms,
+                )
+            };
+            self.current.store(index, Ordering::Relaxed);
+        }
+        Ok(self.screens[previous].uid)
+    }
+
+    // runtime widget creation, should be called from lvgl thread (cf: exec)
+    pub fn create_widget(
+        &self,
+        config: &WidgetConfig,
+        screen: Option<&str>,
+    ) -> Result<Vec<&'static LvglWidget>, DisplayError> {
+        let screen = match screen {
+            Some(uid) => match self.get_screen(uid) {
+                Some(screen) => screen,
+                None => return Err(DisplayError::Config(format!("unknown screen uid:{}", uid))),
+            },
+            None => match self.screens.get(self.current.load(Ordering::Relaxed)) {
+                Some(screen) => screen,
+                None => return Err(DisplayError::Config("display panel not finalized".to_string())),
+            },
+        };
+
+        let mut uids = Vec::new();
+        config.get_panel_uids(&mut uids);
+        for (index, uid) in uids.iter().enumerate() {
+            if self.find_widget(uid).is_some() || uids[..index].contains(uid) {
+                return Err(DisplayError::DuplicateUid(uid.to_string()));
+            }
+        }
+        if self.find_area(config.get_uid()).is_some() {
+            return Err(DisplayError::DuplicateUid(config.get_uid().to_string()));
+        }
+
+        // build only appends to screen container, on failure drop every object created so far
+        let container = get_widget_handle(screen.container)?;
+        let first = unsafe { cglue::capi_obj_get_child_cnt(container) };
+        let mut widgets = Vec::new();
+        let root = match config.build(screen.container, &mut widgets) {
+            Ok(root) => root,
+            Err(error) => {
+                unsafe {
+                    while cglue::capi_obj_get_child_cnt(container) > first {
+                        cglue::capi_obj_del(cglue::capi_obj_get_child(container, first));
+                    }
+                }
+                return Err(DisplayError::Config(error));
+            }
+        };
+        self.register_states(config);
+
+        if let WidgetConfig::Area(_) = config {
+            self.areas
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(CreatedArea {
+                    uid: root.get_uid(),
+                    area: root,
+                    uids: widgets.iter().map(|widget| widget.get_uid()).collect(),
+                });
+        }
+
+        // keep both display and screen pools sorted for binary search
+        let mut panel = write_pool(&self.panel);
+        let mut screen_panel = write_pool(&screen.panel);
+        for widget in &widgets {
+            if let Some(callback) = self.ctrlbox {
+                widget.set_callback(callback);
+            }
+            if let Err(index) = search_pool(&panel, widget.get_uid()) {
+                panel.insert(index, *widget);
+            }
+            if let Err(index) = search_pool(&screen_panel, widget.get_uid()) {
+                screen_panel.insert(index, *widget);
+            }
+        }
+        Ok(widgets)
+    }
+
+    fn find_area(&self, uid: &str) -> Option<&'static LvglWidget> {
+        let areas = self.areas.read().unwrap_or_else(PoisonError::into_inner);
+        areas
+            .iter()
+            .find(|entry| entry.uid == uid)
+            .map(|entry| entry.area)
+    }
+
+    // remove widget from uid index and free lvgl object, should be called from lvgl thread. A runtime
+    // created area is deleted with every widget it holds.
+    pub fn delete_widget(&self, uid: &str) -> Result<&'static LvglWidget, DisplayError> {
+        let widget = match (self.find_area(uid), self.find_widget(uid)) {
+            (Some(area), _) => area,
+            (None, Some(widget)) => widget,
+            (None, None) => return Err(DisplayError::UnknownUid(uid.to_string())),
+        };
+        let handle = get_widget_handle(widget)?;
+
+        let uids = {
+            let mut areas = self.areas.write().unwrap_or_else(PoisonError::into_inner);
+            match areas.iter().position(|entry| entry.uid == uid) {
+                Some(index) => areas.remove(index).uids,
+                None => {
+                    // deleted widget may belong to a runtime area, forget it there too
+                    for entry in areas.iter_mut() {
+                        entry.uids.retain(|value| *value != uid);
+                    }
+                    vec![widget.get_uid()]
+                }
+            }
+        };
+        for uid in uids {
+            self.unindex_widget(uid);
+        }
+
+        // rust widget stays leaked as verb/event context may still reference it
+        unsafe { cglue::capi_obj_del(handle) };
+        Ok(widget)
+    }
+
+    fn unindex_widget(&self, uid: &str) {
         {
-            Ok(index) => self.panel[index].as_any(),
-            Err(_) => &0, // return a dummy value
+            let mut panel = write_pool(&self.panel);
+            if let Ok(index) = search_pool(&panel, uid) {
+                panel.remove(index);
+            }
+        }
+        for screen in &self.screens {
+            let mut panel = write_pool(&screen.panel);
+            if let Ok(index) = search_pool(&panel, uid) {
+                panel.remove(index);
+            }
+        }
+        self.states
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(uid);
+        self.release_image(uid);
+    }
+
+    pub fn draw_config(&mut self, config: &PanelConfig) -> Result<&mut Self, String> {
+        match config {
+            PanelConfig::Demo => self.draw_panel(),
+            PanelConfig::Panel(panel) => self.draw_from_config(panel)?,
+            PanelConfig::Screens(screens) => self.draw_screens(screens)?,
         };
-        widget
+        Ok(self)
+    }
+
+    // build panel from binding json config 'panel' description
+    pub fn draw_from_config(&mut self, panel: &[WidgetConfig]) -> Result<&mut Self, String> {
+        let root = self.get_root();
+        for config in panel {
+            config.build(root, &mut write_pool(&self.panel))?;
+            self.register_states(config);
+        }
+        Ok(self)
+    }
+
+    // each screen is a full display area container holding its own panel
+    pub fn draw_screens(&mut self, screens: &[ScreenConfig]) -> Result<&mut Self, String> {
+        let root = self.get_root();
+        for config in screens {
+            if self.get_screen(config.uid.as_str()).is_some() {
+                return Err(format!("duplicated screen uid:{}", config.uid));
+            }
+            let uid = to_static_str(config.uid.clone());
+            let area = LvglArea::new(root, uid, 0, 0);
+            area.set_size(self.x_res, self.y_res);
+            if let Some(color) = &config.background {
+                area.set_background(color.to_color());
+            }
+            let container = area.finalize();
+            let hidden = !self.screens.is_empty();
+            let handle = get_widget_handle(container)?;
+            unsafe { cglue::capi_screen_init(handle, hidden) };
+
+            let mut panel = Vec::new();
+            for widget in &config.panel {
+                widget.build(container, &mut panel)?;
+                self.register_states(widget);
+            }
+            write_pool(&self.panel).extend(panel.iter());
+            self.screens.push(DisplayScreen {
+                uid,
+                container,
+                panel: RwLock::new(panel),
+            });
+        }
+        Ok(self)
+    }
+
+    // image file displayed at top left corner, indexed by uid so 'image' verb may replace it
+    pub fn draw_logo(&mut self, uid: &'static str, path: &str) -> &mut Self {
+        write_pool(&self.panel).push(LvglImage::new(self.get_root(), uid, path, 0, 0).finalize());
+        self
     }
 
     pub fn draw_panel(&mut self) -> &mut Self {
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglPixButton::new(self.get_root(), "Pixmap-Button", 450, 7)
                 .set_info("Demo Pixmap Button")
                 .set_value(AssetPixmap::nfc_off())
@@ -83,7 +910,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglLabel::new(self.get_root(), "Label-1", LvglMkFont::std_22(), 50, 400)
                 .set_info("Demo Label widget")
                 .set_value("This is a label widget")
@@ -101,18 +928,18 @@ impl DisplayHandle {
             .set_size(370, 70)
             .finalize();
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglPixmap::new(icon_area, "Icon-Charge", LvglIcon::WIFI, 0, 0)
                 .set_info("Demo Wifi Icon")
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglPixmap::new(icon_area, "Icon-Battery", LvglIcon::BATTERY_2, 50, 0)
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglLed::new(icon_area, "Led-Red", 100, 3)
                 .set_info("red led")
                 .set_color(LvglColor::RED())
@@ -121,7 +948,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglLed::new(icon_area, "Led-Green", 150, 3)
                 .set_height(30)
                 .set_color(LvglColor::rvb(0, 255, 0))
@@ -132,7 +959,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglSwitch::new(icon_area, "Switch-1", 200, 0)
                 .set_disable(false)
                 .set_value(false)
@@ -140,7 +967,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglSwitch::new(icon_area, "Switch-2", 260, 0)
                 .set_disable(true)
                 .set_value(true)
@@ -148,7 +975,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglQrcode::new(
                 self.get_root(),
                 "qr-code",
@@ -170,7 +997,7 @@ impl DisplayHandle {
             LvglPoint { x: 180, y: 60 },
             LvglPoint { x: 240, y: 10 },
         ];
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglLine::new(self.get_root(), "Line", 400, 100)
                 .set_color(LvglColor::RED())
                 .set_width(8)
@@ -179,7 +1006,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglButton::new(self.get_root(), "Button-A", LvglMkFont::std_18(), 100, 200)
                 .set_value("My Button-A")
                 .set_info("Push Button 1")
@@ -187,20 +1014,20 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglButton::new(self.get_root(), "Button-B", LvglMkFont::std_14(), 300, 200)
                 .set_info("Push button B")
                 .set_value("My Button-B")
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglArc::new(self.get_root(), "Arc", 10, 270, 800, 150)
                 .set_info("Arc widget")
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglBar::new(self.get_root(), "Bar-1", 10, 90, 700, 300)
                 .set_info("variable bar")
                 .set_size(10, 250)
@@ -209,7 +1036,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglBar::new(self.get_root(), "Bar-2", 10, 90, 400, 300)
                 .set_info("variable bar")
                 .set_size(250, 10)
@@ -218,7 +1045,7 @@ impl DisplayHandle {
                 .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglMeter::new(
                 self.get_root(),
                 "Meter",
@@ -238,7 +1065,7 @@ impl DisplayHandle {
             .finalize(),
         );
 
-        self.panel.push(
+        write_pool(&self.panel).push(
             LvglTextArea::new(self.get_root(), "Text-Area", 0, 550)
                 .set_info("Demo Text area Zone")
                 .set_width(600)
@@ -249,16 +1076,43 @@ impl DisplayHandle {
         self
     }
 
-    pub fn finalize(&mut self) {
+    // index panel without starting lvgl main loop, rendering is then driven by refresh (tests)
+    pub fn finalize_panel(&mut self) -> Result<&mut Self, DisplayError> {
+        // without screen config every widget sit on a single 'main' screen using root as container
+        if self.screens.is_empty() {
+            self.screens.push(DisplayScreen {
+                uid: "main",
+                container: self.get_root(),
+                panel: RwLock::new(self.get_panel()),
+            });
+        }
+
         // sort widget by uid and add them to pannel pool
-        self.panel.sort_by(|a, b| a.get_uid().cmp(&b.get_uid()));
-        for widget in &self.panel {
+        for screen in &self.screens {
+            write_pool(&screen.panel).sort_by(|a, b| a.get_uid().cmp(&b.get_uid()));
+        }
+        let mut panel = write_pool(&self.panel);
+        panel.sort_by(|a, b| a.get_uid().cmp(&b.get_uid()));
+
+        // binary search requires unique uids, reject panel instead of silently hiding a widget
+        if let Some(pair) = panel.windows(2).find(|pair| pair[0].get_uid() == pair[1].get_uid()) {
+            return Err(DisplayError::DuplicateUid(pair[0].get_uid().to_string()));
+        }
+        for widget in panel.iter() {
             match self.ctrlbox {
                 Some(callback) => widget.set_callback(callback),
                 None => {}
             }
         }
-        // start lvgl main loop thread
+        drop(panel);
+        Ok(self)
+    }
+
+    pub fn finalize(&mut self) -> Result<(), DisplayError> {
+        self.finalize_panel()?;
+        // drain verb jobs from lvgl thread, then start lvgl main loop thread
+        self.queue.start();
         self.handle.start_loop();
+        Ok(())
     }
 }
@@ -0,0 +1,147 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::capi::cglue;
+use std::collections::VecDeque;
+use std::ffi::c_void;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+// lvgl timer period used to drain pending jobs
+const QUEUE_PERIOD_MS: u32 = 10;
+// max time a caller waits for lvgl thread to apply its job
+const QUEUE_TIMEOUT: Duration = Duration::from_secs(5);
+
+// a job is either run by lvgl thread or cancelled by its caller on timeout, never both
+const JOB_PENDING: u8 = 0;
+const JOB_RUNNING: u8 = 1;
+const JOB_CANCELLED: u8 = 2;
+
+// jobs capture &'static widgets that are not Send, they are only ever run from lvgl thread (cf: exec safety)
+struct DisplayJob {
+    state: Arc<AtomicU8>,
+    run: Box<dyn FnOnce()>,
+}
+unsafe impl Send for DisplayJob {}
+
+pub struct DisplayQueue {
+    jobs: Mutex<VecDeque<DisplayJob>>,
+    started: AtomicBool,
+    lvgl_thread: OnceLock<ThreadId>,
+}
+
+extern "C" fn queue_timer_cb(context: *mut c_void) {
+    let queue = unsafe { &*(context as *const DisplayQueue) };
+    queue.drain();
+}
+
+impl DisplayQueue {
+    pub(crate) fn new() -> &'static Self {
+        Box::leak(Box::new(DisplayQueue {
+            jobs: Mutex::new(VecDeque::new()),
+            started: AtomicBool::new(false),
+            lvgl_thread: OnceLock::new(),
+        }))
+    }
+
+    // register drain timer, should be called before lvgl main loop starts. Timer runs first on
+    // first loop iteration, lvgl thread is known before any widget callback may call exec.
+    pub(crate) fn start(&'static self) {
+        let context = self as *const DisplayQueue as *mut c_void;
+        unsafe { cglue::capi_timer_create(Some(queue_timer_cb), QUEUE_PERIOD_MS, context) };
+        self.started.store(true, Ordering::Release);
+    }
+
+    // called from lvgl timer, every job pending at this time is applied within the same refresh cycle
+    fn drain(&self) {
+        let _ = self.lvgl_thread.set(thread::current().id());
+        let jobs: Vec<DisplayJob> = match self.jobs.lock() {
+            Ok(mut jobs) => jobs.drain(..).collect(),
+            Err(_) => return,
+        };
+        for job in jobs {
+            let state = job.state.compare_exchange(
+                JOB_PENDING,
+                JOB_RUNNING,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            );
+            if state.is_ok() {
+                (job.run)();
+            }
+        }
+    }
+
+    fn is_inline(&self) -> bool {
+        !self.started.load(Ordering::Acquire)
+            || self.lvgl_thread.get() == Some(&thread::current().id())
+    }
+
+    /// Run job on lvgl thread and wait until it is applied.
+    ///
+    /// # Safety
+    /// Job is moved to lvgl thread while it is not `Send`. It should only capture owned `Send` values
+    /// and `'static` display/widget references, which are only dereferenced from lvgl thread. Non thread
+    /// safe shared state (`Rc`, `Cell`, ...) should never be captured.
+    pub unsafe fn exec<T, F>(&self, job: F) -> Result<T, String>
+    where
+        F: FnOnce() -> T + 'static,
+        T: Send + 'static,
+    {
+        // main loop not started or already within lvgl thread
+        if self.is_inline() {
+            return Ok(job());
+        }
+
+        let (sender, receiver) = mpsc::sync_channel::<T>(1);
+        let state = Arc::new(AtomicU8::new(JOB_PENDING));
+        let job = DisplayJob {
+            state: state.clone(),
+            run: Box::new(move || {
+                let _ = sender.send(job());
+            }),
+        };
+        match self.jobs.lock() {
+            Ok(mut jobs) => jobs.push_back(job),
+            Err(_) => return Err("display queue lock poisoned".to_string()),
+        }
+
+        match receiver.recv_timeout(QUEUE_TIMEOUT) {
+            Ok(result) => Ok(result),
+            Err(RecvTimeoutError::Timeout) => {
+                // cancelled job is skipped by drain, a running one is waited for as its change gets applied
+                let cancel = state.compare_exchange(
+                    JOB_PENDING,
+                    JOB_CANCELLED,
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                );
+                match cancel {
+                    Ok(_) => Err("display queue job not applied: timeout".to_string()),
+                    Err(_) => match receiver.recv() {
+                        Ok(result) => Ok(result),
+                        Err(error) => Err(format!("display queue job not applied: {}", error)),
+                    },
+                }
+            }
+            Err(error) => Err(format!("display queue job not applied: {}", error)),
+        }
+    }
+}
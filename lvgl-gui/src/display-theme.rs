@@ -0,0 +1,136 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::prelude::*;
+use lvgl::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+
+// lvgl std fonts are montserrat, only sizes compiled within lv_conf.h are available
+#[derive(Deserialize, Debug, Clone)]
+pub struct FontConfig {
+    #[serde(default = "default_family")]
+    pub family: String,
+    #[serde(default = "default_size")]
+    pub size: u16,
+}
+
+fn default_family() -> String {
+    "montserrat".to_string()
+}
+
+fn default_size() -> u16 {
+    14
+}
+
+impl Default for FontConfig {
+    fn default() -> Self {
+        FontConfig {
+            family: default_family(),
+            size: default_size(),
+        }
+    }
+}
+
+impl FontConfig {
+    pub fn to_font(&self) -> Result<&'static LvglFont, String> {
+        match self.family.to_lowercase().as_str() {
+            "std" | "montserrat" => get_font(format!("std_{}", self.size).as_str()),
+            _ => Err(format!("unsupported font family:{} (montserrat)", self.family)),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ThemeConfig {
+    #[serde(default = "default_uid")]
+    pub uid: String,
+    #[serde(default)]
+    pub dark: bool,
+    pub primary: ColorConfig,
+    pub secondary: ColorConfig,
+    #[serde(default)]
+    pub font: FontConfig,
+}
+
+fn default_uid() -> String {
+    "custom".to_string()
+}
+
+impl ThemeConfig {
+    fn new(uid: &str, dark: bool, primary: &str, secondary: &str, size: u16) -> Self {
+        ThemeConfig {
+            uid: uid.to_string(),
+            dark,
+            primary: primary.parse().unwrap_or(ColorConfig::from_hex(0)),
+            secondary: secondary.parse().unwrap_or(ColorConfig::from_hex(0)),
+            font: FontConfig {
+                family: default_family(),
+                size,
+            },
+        }
+    }
+
+    // built-in themes, binding config 'themes' may overload them
+    pub fn defaults() -> Vec<ThemeConfig> {
+        vec![
+            ThemeConfig::new("day", false, "light_blue", "blue_grey", 14),
+            ThemeConfig::new("night", true, "indigo", "blue_grey", 14),
+            ThemeConfig::new("high-contrast", true, "yellow", "white", 18),
+        ]
+    }
+}
+
+pub fn theme_from_json(json: &str) -> Result<ThemeConfig, String> {
+    match serde_json::from_str::<ThemeConfig>(json) {
+        Ok(theme) => Ok(theme),
+        Err(error) => Err(format!("invalid theme config: {}", error)),
+    }
+}
+
+pub fn themes_from_json(json: &str) -> Result<Vec<ThemeConfig>, String> {
+    match serde_json::from_str::<Vec<ThemeConfig>>(json) {
+        Ok(themes) => Ok(themes),
+        Err(error) => Err(format!("invalid themes config: {}", error)),
+    }
+}
+
+// binding config theme rules: built-in themes, 'themes' overload them, 'theme' is a uid or an inline theme (default day)
+pub fn themes_from_config(config: &Value) -> Result<(Vec<ThemeConfig>, String), String> {
+    let mut themes = ThemeConfig::defaults();
+    if let Some(jthemes) = config.get("themes") {
+        for theme in themes_from_json(jthemes.to_string().as_str())? {
+            themes.retain(|config| config.uid != theme.uid);
+            themes.push(theme);
+        }
+    }
+    let current = match config.get("theme") {
+        Some(Value::String(uid)) => uid.clone(),
+        Some(jtheme) => {
+            let theme = theme_from_json(jtheme.to_string().as_str())?;
+            let uid = theme.uid.clone();
+            themes.retain(|config| config.uid != uid);
+            themes.push(theme);
+            uid
+        }
+        None => "day".to_string(),
+    };
+    if !themes.iter().any(|theme| theme.uid == current) {
+        return Err(format!("unknown theme uid:{}", current));
+    }
+    Ok((themes, current))
+}
@@ -21,9 +21,49 @@ mod capi;
 #[path = "display-lvgl.rs"]
 mod display;
 
+#[path = "display-queue.rs"]
+mod queue;
+
+#[path = "display-theme.rs"]
+mod theme;
+
+#[path = "display-image.rs"]
+mod image;
+
+#[path = "display-journal.rs"]
+mod journal;
+
+#[path = "display-input.rs"]
+mod input;
+
+#[path = "display-evdev.rs"]
+mod evdev;
+
+#[path = "panel-config.rs"]
+mod config;
+
+#[path = "config-schema.rs"]
+mod schema;
+
+#[path = "widget-state.rs"]
+mod state;
+
+#[path = "widget-batch.rs"]
+mod batch;
+
 pub mod prelude {
     //pub(crate) use crate::capi::*;
+    pub use crate::batch::*;
     pub use crate::capi::AssetPixmap;
+    pub use crate::config::*;
     pub use crate::display::*;
+    pub use crate::evdev::*;
+    pub use crate::image::*;
+    pub use crate::input::*;
+    pub use crate::journal::*;
+    pub use crate::queue::*;
+    pub use crate::schema::*;
+    pub use crate::state::*;
+    pub use crate::theme::*;
     pub use lvgl::prelude::*;
 }
\ No newline at end of file
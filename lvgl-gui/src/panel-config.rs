@@ -0,0 +1,722 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::prelude::*;
+use lvgl::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+// widget uid/label live as long as the display, config strings are leaked once at build time
+pub fn to_static_str(value: String) -> &'static str {
+    Box::leak(value.into_boxed_str())
+}
+
+// color is either {red,green,blue}, "#RRGGBB" or a named palette color ("light_blue", "blue_grey", ...)
+#[derive(Deserialize, Debug, Clone)]
+#[serde(try_from = "ColorValue")]
+pub struct ColorConfig {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ColorValue {
+    Rvb { red: u8, green: u8, blue: u8 },
+    Text(String),
+}
+
+// lvgl palette main colors (lv_palette_main)
+const PALETTE: [(&str, u32); 21] = [
+    ("red", 0xF44336),
+    ("pink", 0xE91E63),
+    ("purple", 0x9C27B0),
+    ("deep_purple", 0x673AB7),
+    ("indigo", 0x3F51B5),
+    ("blue", 0x2196F3),
+    ("light_blue", 0x03A9F4),
+    ("cyan", 0x00BCD4),
+    ("teal", 0x009688),
+    ("green", 0x4CAF50),
+    ("light_green", 0x8BC34A),
+    ("lime", 0xCDDC39),
+    ("yellow", 0xFFEB3B),
+    ("amber", 0xFFC107),
+    ("orange", 0xFF9800),
+    ("deep_orange", 0xFF5722),
+    ("brown", 0x795548),
+    ("blue_grey", 0x607D8B),
+    ("grey", 0x9E9E9E),
+    ("black", 0x000000),
+    ("white", 0xFFFFFF),
+];
+
+impl TryFrom<ColorValue> for ColorConfig {
+    type Error = String;
+    fn try_from(value: ColorValue) -> Result<Self, Self::Error> {
+        match value {
+            ColorValue::Rvb { red, green, blue } => Ok(ColorConfig { red, green, blue }),
+            ColorValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+impl FromStr for ColorConfig {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        if let Some(hex) = text.strip_prefix('#') {
+            return match u32::from_str_radix(hex, 16) {
+                Ok(value) if hex.len() == 6 => Ok(ColorConfig::from_hex(value)),
+                _ => Err(format!("invalid color:{} (expect #RRGGBB)", text)),
+            };
+        }
+        let name = text.to_lowercase().replace('-', "_");
+        match PALETTE.iter().find(|(label, _)| *label == name) {
+            Some((_, hex)) => Ok(ColorConfig::from_hex(*hex)),
+            None => Err(format!("unknown color:{} (#RRGGBB or palette name)", text)),
+        }
+    }
+}
+
+impl ColorConfig {
+    pub fn from_hex(hex: u32) -> Self {
+        ColorConfig {
+            red: (hex >> 16) as u8,
+            green: (hex >> 8) as u8,
+            blue: hex as u8,
+        }
+    }
+
+    pub fn to_color(&self) -> LvglColor {
+        LvglColor::rvb(self.red, self.green, self.blue)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BorderConfig {
+    pub width: i16,
+    pub color: ColorConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TitleConfig {
+    pub text: String,
+    #[serde(default)]
+    pub x: i16,
+    #[serde(default)]
+    pub y: i16,
+    pub font: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PointConfig {
+    pub x: i16,
+    pub y: i16,
+}
+
+// attributes shared by every widget type
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommonConfig {
+    pub uid: String,
+    pub info: Option<String>,
+    #[serde(default)]
+    pub x: i16,
+    #[serde(default)]
+    pub y: i16,
+    pub width: Option<i16>,
+    pub height: Option<i16>,
+    pub color: Option<ColorConfig>,
+    pub background: Option<ColorConfig>,
+    pub border: Option<BorderConfig>,
+    pub title: Option<TitleConfig>,
+    pub disable: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LabelConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub font: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ButtonConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub font: Option<String>,
+    pub value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BarConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    #[serde(default)]
+    pub min: i32,
+    #[serde(default = "default_max")]
+    pub max: i32,
+    pub gradient: Option<GradientConfig>,
+    pub value: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GradientConfig {
+    #[serde(default)]
+    pub vertical: bool,
+    pub start: ColorConfig,
+    pub end: ColorConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TicConfig {
+    pub width: i32,
+    pub length: i32,
+    pub count: i32,
+    pub major_length: i32,
+    pub major_nth: i32,
+    pub color: ColorConfig,
+    pub major_color: ColorConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ZoneConfig {
+    pub start: i32,
+    pub end: i32,
+    pub width: i32,
+    pub color: ColorConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MeterConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub needle_width: i32,
+    pub needle_length: i32,
+    pub needle_color: ColorConfig,
+    pub tic: Option<TicConfig>,
+    #[serde(default)]
+    pub zones: Vec<ZoneConfig>,
+    pub value: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LedConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub brightness: Option<u8>,
+    pub value: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct SwitchConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub value: Option<bool>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct ArcConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    #[serde(default)]
+    pub start: i32,
+    #[serde(default = "default_angle")]
+    pub end: i32,
+    pub value: Option<i32>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct QrcodeConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub dark: ColorConfig,
+    pub light: ColorConfig,
+    pub size: i16,
+    pub value: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LineConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub line_width: Option<i16>,
+    #[serde(default)]
+    pub rounded: bool,
+    pub points: Vec<PointConfig>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TextAreaConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub value: Option<String>,
+}
+
+// optional states map widget state to icon name, ex: {"on":"WIFI", "off":"CLOSE"}
+#[derive(Deserialize, Debug, Clone)]
+pub struct PixmapConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub icon: String,
+    #[serde(default)]
+    pub states: HashMap<String, String>,
+}
+
+// optional states map widget state to asset name, ex: {"on":"nfc_on", "off":"nfc_off"}
+#[derive(Deserialize, Debug, Clone)]
+pub struct PixButtonConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    pub value: Option<String>,
+    #[serde(default)]
+    pub states: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct AreaConfig {
+    #[serde(flatten)]
+    pub common: CommonConfig,
+    #[serde(default)]
+    pub widgets: Vec<WidgetConfig>,
+}
+
+fn default_max() -> i32 {
+    100
+}
+
+fn default_angle() -> i32 {
+    360
+}
+
+// one entry of binding config 'panel' array, widget type is selected by 'type' key
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WidgetConfig {
+    Label(LabelConfig),
+    Button(ButtonConfig),
+    Bar(BarConfig),
+    Meter(MeterConfig),
+    Led(LedConfig),
+    Switch(SwitchConfig),
+    Arc(ArcConfig),
+    Qrcode(QrcodeConfig),
+    Line(LineConfig),
+    Textarea(TextAreaConfig),
+    Pixmap(PixmapConfig),
+    Pixbutton(PixButtonConfig),
+    Area(AreaConfig),
+}
+
+// one named screen with its own panel, only one screen is displayed at a time
+#[derive(Deserialize, Debug, Clone)]
+pub struct ScreenConfig {
+    pub uid: String,
+    pub background: Option<ColorConfig>,
+    #[serde(default)]
+    pub panel: Vec<WidgetConfig>,
+}
+
+// binding config either describe a single panel, several screens or fallback to built-in demo
+pub enum PanelConfig {
+    Demo,
+    Panel(Vec<WidgetConfig>),
+    Screens(Vec<ScreenConfig>),
+}
+
+pub fn screens_from_json(json: &str) -> Result<Vec<ScreenConfig>, String> {
+    match serde_json::from_str::<Vec<ScreenConfig>>(json) {
+        Ok(screens) => Ok(screens),
+        Err(error) => Err(format!("invalid screens config: {}", error)),
+    }
+}
+
+// runtime widget creation, widget is added to given screen or to displayed one
+#[derive(Deserialize, Debug, Clone)]
+pub struct WidgetCreateConfig {
+    pub screen: Option<String>,
+    pub widget: WidgetConfig,
+}
+
+pub fn create_from_json(json: &str) -> Result<WidgetCreateConfig, String> {
+    match serde_json::from_str::<WidgetCreateConfig>(json) {
+        Ok(config) => Ok(config),
+        Err(error) => Err(format!("invalid widget config: {}", error)),
+    }
+}
+
+pub fn panel_from_json(json: &str) -> Result<Vec<WidgetConfig>, String> {
+    match serde_json::from_str::<Vec<WidgetConfig>>(json) {
+        Ok(panel) => Ok(panel),
+        Err(error) => Err(format!("invalid panel config: {}", error)),
+    }
+}
+
+pub fn get_font(name: &str) -> Result<&'static LvglFont, String> {
+    let font = match name {
+        "std_10" => LvglMkFont::std_10(),
+        "std_14" => LvglMkFont::std_14(),
+        "std_18" => LvglMkFont::std_18(),
+        "std_22" => LvglMkFont::std_22(),
+        _ => return Err(format!("unsupported font:{} (std_10|std_14|std_18|std_22)", name)),
+    };
+    Ok(font)
+}
+
+// resolve state name to image name, unknown state is used as image name
+pub fn get_state<'a>(states: &'a HashMap<String, String>, value: &'a str) -> &'a str {
+    match states
+        .iter()
+        .find(|(state, _)| state.eq_ignore_ascii_case(value))
+    {
+        Some((_, image)) => image.as_str(),
+        None => value,
+    }
+}
+
+// any compiled asset, cf: AssetPixmap::names()
+pub fn get_asset(name: &str) -> Result<&'static LvglImgDsc, String> {
+    match AssetPixmap::by_name(name) {
+        Some(asset) => Ok(asset),
+        None => Err(format!("unknown pixmap asset:{}", name)),
+    }
+}
+
+pub fn get_icon(name: &str) -> Result<&'static str, String> {
+    let icon = match name {
+        "WIFI" => LvglIcon::WIFI,
+        "BATTERY_FULL" => LvglIcon::BATTERY_FULL,
+        "BATTERY_3" => LvglIcon::BATTERY_3,
+        "BATTERY_2" => LvglIcon::BATTERY_2,
+        "BATTERY_1" => LvglIcon::BATTERY_1,
+        "BATTERY_EMPTY" => LvglIcon::BATTERY_EMPTY,
+        "CHARGE" => LvglIcon::CHARGE,
+        "BLUETOOTH" => LvglIcon::BLUETOOTH,
+        "USB" => LvglIcon::USB,
+        "GPS" => LvglIcon::GPS,
+        "BELL" => LvglIcon::BELL,
+        "WARNING" => LvglIcon::WARNING,
+        "OK" => LvglIcon::OK,
+        "CLOSE" => LvglIcon::CLOSE,
+        "POWER" => LvglIcon::POWER,
+        "SETTINGS" => LvglIcon::SETTINGS,
+        "HOME" => LvglIcon::HOME,
+        "REFRESH" => LvglIcon::REFRESH,
+        _ => return Err(format!("unknown pixmap icon:{}", name)),
+    };
+    Ok(icon)
+}
+
+// apply optional attributes shared by every widget type
+macro_rules! set_common {
+    ($widget:ident, $common:expr) => {
+        if let Some(info) = &$common.info {
+            $widget.set_info(to_static_str(info.clone()));
+        }
+        match ($common.width, $common.height) {
+            (Some(width), Some(height)) => {
+                $widget.set_size(width, height);
+            }
+            (Some(width), None) => {
+                $widget.set_width(width);
+            }
+            (None, Some(height)) => {
+                $widget.set_height(height);
+            }
+            (None, None) => {}
+        }
+        if let Some(color) = &$common.color {
+            $widget.set_color(color.to_color());
+        }
+        if let Some(color) = &$common.background {
+            $widget.set_background(color.to_color());
+        }
+        if let Some(border) = &$common.border {
+            $widget.set_border(border.width, border.color.to_color());
+        }
+        if let Some(title) = &$common.title {
+            let font = match &title.font {
+                Some(font) => get_font(font)?,
+                None => LvglMkFont::std_10(),
+            };
+            $widget.set_title(to_static_str(title.text.clone()), title.x, title.y, font);
+        }
+        if let Some(disable) = $common.disable {
+            $widget.set_disable(disable);
+        }
+    };
+}
+
+impl WidgetConfig {
+    pub fn get_common(&self) -> &CommonConfig {
+        match self {
+            WidgetConfig::Label(config) => &config.common,
+            WidgetConfig::Button(config) => &config.common,
+            WidgetConfig::Bar(config) => &config.common,
+            WidgetConfig::Meter(config) => &config.common,
+            WidgetConfig::Led(config) => &config.common,
+            WidgetConfig::Switch(config) => &config.common,
+            WidgetConfig::Arc(config) => &config.common,
+            WidgetConfig::Qrcode(config) => &config.common,
+            WidgetConfig::Line(config) => &config.common,
+            WidgetConfig::Textarea(config) => &config.common,
+            WidgetConfig::Pixmap(config) => &config.common,
+            WidgetConfig::Pixbutton(config) => &config.common,
+            WidgetConfig::Area(config) => &config.common,
+        }
+    }
+
+    pub fn get_uid(&self) -> &str {
+        self.get_common().uid.as_str()
+    }
+
+    // config 'type' key, same names as get_widget_type
+    pub fn get_type(&self) -> &'static str {
+        match self {
+            WidgetConfig::Label(_) => "label",
+            WidgetConfig::Button(_) => "button",
+            WidgetConfig::Bar(_) => "bar",
+            WidgetConfig::Meter(_) => "meter",
+            WidgetConfig::Led(_) => "led",
+            WidgetConfig::Switch(_) => "switch",
+            WidgetConfig::Arc(_) => "arc",
+            WidgetConfig::Qrcode(_) => "qrcode",
+            WidgetConfig::Line(_) => "line",
+            WidgetConfig::Textarea(_) => "textarea",
+            WidgetConfig::Pixmap(_) => "pixmap",
+            WidgetConfig::Pixbutton(_) => "pixbutton",
+            WidgetConfig::Area(_) => "area",
+        }
+    }
+
+    // pixmap/pixbutton state to image map, state names are case insensitive
+    pub fn get_states(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            WidgetConfig::Pixmap(config) => Some(&config.states),
+            WidgetConfig::Pixbutton(config) => Some(&config.states),
+            _ => None,
+        }
+    }
+
+    // walk config including area children
+    pub fn for_each<'a>(&'a self, callback: &mut dyn FnMut(&'a WidgetConfig)) {
+        match self {
+            WidgetConfig::Area(config) => {
+                for child in &config.widgets {
+                    child.for_each(callback);
+                }
+            }
+            _ => callback(self),
+        }
+    }
+
+    // uids of every widget pushed into panel by build, including area children
+    pub fn get_panel_uids<'a>(&'a self, uids: &mut Vec<&'a str>) {
+        self.for_each(&mut |config| uids.push(config.get_uid()));
+    }
+
+    // create lvgl widget(s) and push them into panel, area are only used as parent. Returns top level
+    // widget, area included.
+    pub fn build(
+        &self,
+        parent: &'static LvglWidget,
+        panel: &mut Vec<&'static LvglWidget>,
+    ) -> Result<&'static LvglWidget, String> {
+        let uid = to_static_str(self.get_uid().to_string());
+        let common = self.get_common();
+
+        let widget = match self {
+            WidgetConfig::Label(config) => {
+                let font = match &config.font {
+                    Some(font) => get_font(font)?,
+                    None => LvglMkFont::std_14(),
+                };
+                let widget = LvglLabel::new(parent, uid, font, common.x, common.y);
+                if let Some(value) = &config.value {
+                    widget.set_value(value.as_str());
+                }
+                set_common!(widget, common);
+                widget.finalize()
+            }
+            WidgetConfig::Button(config) => {
+                let font = match &config.font {
+                    Some(font) => get_font(font)?,
+                    None => LvglMkFont::std_14(),
+                };
+                let widget = LvglButton::new(parent, uid, font, common.x, common.y);
+                if let Some(value) = &config.value {
+                    widget.set_value(value.as_str());
+                }
+                set_common!(widget, common);
+                widget.finalize()
+            }
+            WidgetConfig::Bar(config) => {
+                let widget = LvglBar::new(parent, uid, config.min, config.max, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(gradient) = &config.gradient {
+                    widget.set_gradient(
+                        gradient.vertical,
+                        gradient.start.to_color(),
+                        gradient.end.to_color(),
+                    );
+                }
+                if let Some(value) = config.value {
+                    widget.set_value(value);
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Meter(config) => {
+                let widget = LvglMeter::new(
+                    parent,
+                    uid,
+                    config.needle_width,
+                    config.needle_length,
+                    config.needle_color.to_color(),
+                    common.x,
+                    common.y,
+                );
+                set_common!(widget, common);
+                if let Some(tic) = &config.tic {
+                    widget.set_tic(
+                        tic.width,
+                        tic.length,
+                        tic.count,
+                        tic.major_length,
+                        tic.major_nth,
+                        tic.color.to_color(),
+                        tic.major_color.to_color(),
+                    );
+                }
+                for zone in &config.zones {
+                    widget.set_zone(zone.start, zone.end, zone.width, zone.color.to_color());
+                }
+                if let Some(value) = config.value {
+                    widget.set_value(value);
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Led(config) => {
+                let widget = LvglLed::new(parent, uid, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(brightness) = config.brightness {
+                    widget.set_brightness(brightness);
+                }
+                if let Some(value) = config.value {
+                    widget.set_on(value);
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Switch(config) => {
+                let widget = LvglSwitch::new(parent, uid, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(value) = config.value {
+                    widget.set_value(value);
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Arc(config) => {
+                let widget =
+                    LvglArc::new(parent, uid, config.start, config.end, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(value) = config.value {
+                    widget.set_value(value);
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Qrcode(config) => {
+                let widget = LvglQrcode::new(
+                    parent,
+                    uid,
+                    config.dark.to_color(),
+                    config.light.to_color(),
+                    config.size,
+                    common.x,
+                    common.y,
+                );
+                set_common!(widget, common);
+                if let Some(value) = &config.value {
+                    widget.set_value(value.as_str());
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Line(config) => {
+                // line object size follows its points, 'width' would silently override 'line_width'
+                if common.width.is_some() {
+                    return Err(format!(
+                        "widget uid:{} line does not support 'width', use 'line_width'",
+                        uid
+                    ));
+                }
+                let points: Vec<LvglPoint> = config
+                    .points
+                    .iter()
+                    .map(|point| LvglPoint {
+                        x: point.x,
+                        y: point.y,
+                    })
+                    .collect();
+                let widget = LvglLine::new(parent, uid, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(width) = config.line_width {
+                    widget.set_width(width);
+                }
+                widget
+                    .set_rounded(config.rounded)
+                    .set_points(points.into_boxed_slice());
+                widget.finalize()
+            }
+            WidgetConfig::Textarea(config) => {
+                let widget = LvglTextArea::new(parent, uid, common.x, common.y);
+                set_common!(widget, common);
+                if let Some(value) = &config.value {
+                    widget.set_value(value.as_str());
+                }
+                widget.finalize()
+            }
+            WidgetConfig::Pixmap(config) => {
+                let icon = get_icon(get_state(&config.states, config.icon.as_str()))?;
+                let widget = LvglPixmap::new(parent, uid, icon, common.x, common.y);
+                set_common!(widget, common);
+                widget.finalize()
+            }
+            WidgetConfig::Pixbutton(config) => {
+                let widget = LvglPixButton::new(parent, uid, common.x, common.y);
+                if let Some(value) = &config.value {
+                    widget.set_value(get_asset(get_state(&config.states, value.as_str()))?);
+                }
+                set_common!(widget, common);
+                widget.finalize()
+            }
+            WidgetConfig::Area(config) => {
+                // area are not pushed within panel as they do not handle any method
+                let widget = LvglArea::new(parent, uid, common.x, common.y);
+                set_common!(widget, common);
+                let area = widget.finalize();
+                for child in &config.widgets {
+                    child.build(area, panel)?;
+                }
+                return Ok(area);
+            }
+        };
+
+        panel.push(widget);
+        Ok(widget)
+    }
+}
@@ -0,0 +1,253 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::capi::cglue;
+use crate::prelude::*;
+use lvgl::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum BatchAction {
+    Set,
+    Show,
+    Hide,
+    Enable,
+    Disable,
+}
+
+// one widget update as received from batch verb
+#[derive(Deserialize, Serialize, Debug, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct BatchOperation {
+    pub uid: String,
+    #[serde(default = "default_action")]
+    pub action: BatchAction,
+    #[serde(default)]
+    pub value: Value,
+}
+
+fn default_action() -> BatchAction {
+    BatchAction::Set
+}
+
+pub fn batch_from_json(json: &str) -> Result<Vec<BatchOperation>, String> {
+    match serde_json::from_str::<Vec<BatchOperation>>(json) {
+        Ok(batch) => Ok(batch),
+        Err(error) => Err(format!("invalid batch: {}", error)),
+    }
+}
+
+// validated operation, ready to be applied from lvgl thread
+enum BatchValue {
+    Text(String),
+    Int(i32),
+    OnOff(bool),
+    Asset(&'static LvglImgDsc),
+    Icon(&'static str),
+    Hidden(bool),
+    Disabled(bool),
+}
+
+pub struct BatchUpdate {
+    widget: &'static LvglWidget,
+    value: BatchValue,
+}
+
+fn value_to_onoff(value: &Value) -> Option<bool> {
+    match value {
+        Value::Bool(value) => Some(*value),
+        Value::String(value) => match value.to_uppercase().as_str() {
+            "ON" => Some(true),
+            "OFF" => Some(false),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl BatchUpdate {
+    // check widget exists and value matches widget verb input
+    pub fn new(display: &DisplayHandle, operation: &BatchOperation) -> Result<Self, String> {
+        let widget = match display.find_widget(operation.uid.as_str()) {
+            Some(widget) => widget,
+            None => return Err(DisplayError::UnknownUid(operation.uid.clone()).to_string()),
+        };
+
+        if operation.action != BatchAction::Set {
+            get_widget_handle(widget)?;
+        }
+        let value = match operation.action {
+            BatchAction::Show => BatchValue::Hidden(false),
+            BatchAction::Hide => BatchValue::Hidden(true),
+            BatchAction::Enable => BatchValue::Disabled(false),
+            BatchAction::Disable => BatchValue::Disabled(true),
+            BatchAction::Set => {
+                let input = get_widget_input(widget);
+                let value = match input {
+                    "text" => operation.value.as_str().map(|text| BatchValue::Text(text.to_string())),
+                    "i32" => operation
+                        .value
+                        .as_i64()
+                        .and_then(|value| i32::try_from(value).ok())
+                        .map(BatchValue::Int),
+                    "onoff" => value_to_onoff(&operation.value).map(BatchValue::OnOff),
+                    "pixmap" => match operation.value.as_str() {
+                        Some(value) => {
+                            let image = display.get_pixmap_image(operation.uid.as_str(), value);
+                            match widget {
+                                LvglWidget::Pixmap(_) => Some(BatchValue::Icon(get_icon(image.as_str())?)),
+                                _ => Some(BatchValue::Asset(get_asset(image.as_str())?)),
+                            }
+                        }
+                        None => None,
+                    },
+                    _ => {
+                        return Err(format!(
+                            "widget uid:{} type:{} has no settable value",
+                            operation.uid,
+                            get_widget_type(widget)
+                        ))
+                    }
+                };
+                match value {
+                    Some(value) => value,
+                    None => {
+                        return Err(format!(
+                            "widget uid:{} expect {} value, got:{}",
+                            operation.uid, input, operation.value
+                        ))
+                    }
+                }
+            }
+        };
+        Ok(BatchUpdate { widget, value })
+    }
+
+    // should only be called from lvgl thread (cf: DisplayHandle::exec)
+    pub fn apply(&self) {
+        match (&self.value, self.widget) {
+            // handle was checked at validation time
+            (BatchValue::Hidden(hidden), widget) => {
+                if let Ok(handle) = get_widget_handle(widget) {
+                    unsafe { cglue::capi_obj_set_hidden(handle, *hidden) }
+                }
+            }
+            (BatchValue::Disabled(disabled), widget) => {
+                if let Ok(handle) = get_widget_handle(widget) {
+                    unsafe { cglue::capi_obj_set_disabled(handle, *disabled) }
+                }
+            }
+            (BatchValue::Text(text), LvglWidget::Label(this)) => {
+                this.set_value(text.as_str());
+            }
+            (BatchValue::Text(text), LvglWidget::TextArea(this)) => {
+                this.set_value(text.as_str());
+            }
+            (BatchValue::Text(text), LvglWidget::Button(this)) => {
+                this.set_value(text.as_str());
+            }
+            (BatchValue::Text(text), LvglWidget::Qrcode(this)) => {
+                this.set_value(text.as_str());
+            }
+            (BatchValue::Int(value), LvglWidget::Bar(this)) => {
+                this.set_value(*value);
+            }
+            (BatchValue::Int(value), LvglWidget::Arc(this)) => {
+                this.set_value(*value);
+            }
+            (BatchValue::Int(value), LvglWidget::Meter(this)) => {
+                this.set_value(*value);
+            }
+            (BatchValue::OnOff(value), LvglWidget::Switch(this)) => {
+                this.set_value(*value);
+            }
+            (BatchValue::OnOff(value), LvglWidget::Led(this)) => {
+                this.set_on(*value);
+            }
+            (BatchValue::Asset(asset), LvglWidget::PixButton(this)) => {
+                this.set_value(*asset);
+            }
+            (BatchValue::Icon(icon), LvglWidget::Pixmap(this)) => {
+                this.set_value(*icon);
+            }
+            _ => {} // value kind was checked against widget input at validation time
+        }
+    }
+}
+
+// per operation status returned to batch verb client
+#[derive(Serialize, Debug, Clone)]
+pub struct BatchResult {
+    pub uid: String,
+    pub action: BatchAction,
+    pub status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn new(operation: &BatchOperation, status: &'static str, error: Option<String>) -> Self {
+        BatchResult {
+            uid: operation.uid.clone(),
+            action: operation.action,
+            status,
+            error,
+        }
+    }
+}
+
+// validate every operation, then apply all of them or none, should be called from lvgl thread
+pub fn batch_apply(
+    display: &DisplayHandle,
+    operations: &[BatchOperation],
+) -> Result<Vec<BatchResult>, Vec<BatchResult>> {
+    let mut updates = Vec::new();
+    let mut results = Vec::new();
+    let mut rejected = false;
+    for operation in operations {
+        match BatchUpdate::new(display, operation) {
+            Ok(update) => {
+                updates.push(update);
+                results.push(BatchResult::new(operation, "applied", None));
+            }
+            Err(error) => {
+                rejected = true;
+                results.push(BatchResult::new(operation, "rejected", Some(error)));
+            }
+        }
+    }
+
+    if rejected {
+        for result in results.iter_mut().filter(|result| result.status == "applied") {
+            result.status = "skipped";
+        }
+        return Err(results);
+    }
+    for (update, operation) in updates.iter().zip(operations) {
+        update.apply();
+        if let BatchValue::Asset(_) | BatchValue::Icon(_) = update.value {
+            display.release_image(operation.uid.as_str());
+        }
+    }
+    Ok(results)
+}
+
+pub fn batch_results_to_json(results: &[BatchResult]) -> String {
+    serde_json::to_string(results).unwrap_or_default()
+}
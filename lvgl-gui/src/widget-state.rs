@@ -0,0 +1,298 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::capi::cglue;
+use crate::prelude::*;
+use lvgl::prelude::*;
+use serde::Serialize;
+use std::any::{Any, TypeId};
+use std::ffi::{c_void, CStr};
+use std::os::raw::c_char;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum WidgetValue {
+    Bool(bool),
+    Int(i32),
+    Text(String),
+    None,
+}
+
+fn cstr_to_value(text: *const c_char) -> WidgetValue {
+    if text.is_null() {
+        WidgetValue::None
+    } else {
+        let text = unsafe { CStr::from_ptr(text) };
+        WidgetValue::Text(text.to_string_lossy().to_string())
+    }
+}
+
+pub fn get_widget_type(widget: &LvglWidget) -> &'static str {
+    match widget {
+        LvglWidget::Label(_) => "label",
+        LvglWidget::Button(_) => "button",
+        LvglWidget::Bar(_) => "bar",
+        LvglWidget::Meter(_) => "meter",
+        LvglWidget::Led(_) => "led",
+        LvglWidget::Switch(_) => "switch",
+        LvglWidget::Arc(_) => "arc",
+        LvglWidget::Qrcode(_) => "qrcode",
+        LvglWidget::Line(_) => "line",
+        LvglWidget::TextArea(_) => "textarea",
+        LvglWidget::Pixmap(_) => "pixmap",
+        LvglWidget::PixButton(_) => "pixbutton",
+        LvglWidget::Area(_) => "area",
+        LvglWidget::Image(_) => "image",
+        _ => "unknown",
+    }
+}
+
+// typed lookup name, same as get_widget_type (cf: DisplayHandle::get_widget)
+pub(crate) fn get_type_name<T: Any>() -> &'static str {
+    let id = TypeId::of::<T>();
+    let types = [
+        (TypeId::of::<LvglLabel>(), "label"),
+        (TypeId::of::<LvglButton>(), "button"),
+        (TypeId::of::<LvglBar>(), "bar"),
+        (TypeId::of::<LvglMeter>(), "meter"),
+        (TypeId::of::<LvglLed>(), "led"),
+        (TypeId::of::<LvglSwitch>(), "switch"),
+        (TypeId::of::<LvglArc>(), "arc"),
+        (TypeId::of::<LvglQrcode>(), "qrcode"),
+        (TypeId::of::<LvglLine>(), "line"),
+        (TypeId::of::<LvglTextArea>(), "textarea"),
+        (TypeId::of::<LvglPixmap>(), "pixmap"),
+        (TypeId::of::<LvglPixButton>(), "pixbutton"),
+        (TypeId::of::<LvglArea>(), "area"),
+        (TypeId::of::<LvglImage>(), "image"),
+    ];
+    match types.iter().find(|(value, _)| *value == id) {
+        Some((_, name)) => name,
+        None => "unknown",
+    }
+}
+
+// stable event name, same as capi-map.c capi_events table (used by event filters and journal replay)
+pub fn get_event_name(event: &LvglEvent) -> &'static str {
+    match event {
+        LvglEvent::PRESSED => "pressed",
+        LvglEvent::PRESSING => "pressing",
+        LvglEvent::PRESS_LOST => "presslost",
+        LvglEvent::SHORT_CLICKED => "shortclicked",
+        LvglEvent::LONG_PRESSED => "longpressed",
+        LvglEvent::LONG_PRESSED_REPEAT => "longpressedrepeat",
+        LvglEvent::CLICKED => "clicked",
+        LvglEvent::RELEASED => "released",
+        LvglEvent::FOCUSED => "focused",
+        LvglEvent::DEFOCUSED => "defocused",
+        LvglEvent::LEAVE => "leave",
+        LvglEvent::VALUE_CHANGED => "valuechanged",
+        LvglEvent::INSERT => "insert",
+        LvglEvent::REFRESH => "refresh",
+        LvglEvent::READY => "ready",
+        LvglEvent::CANCEL => "cancel",
+        #[allow(unreachable_patterns)]
+        _ => "unknown",
+    }
+}
+
+macro_rules! widget_handle {
+    ($widget:expr, $($variant:ident),*) => {
+        match $widget {
+            $(LvglWidget::$variant(this) => this.get_handle() as *mut c_void,)*
+            #[allow(unreachable_patterns)]
+            _ => std::ptr::null_mut(),
+        }
+    };
+}
+
+// raw lvgl object behind a widget, only used to call capi glue
+pub(crate) fn get_widget_handle(widget: &LvglWidget) -> Result<*mut c_void, DisplayError> {
+    let handle = widget_handle!(
+        widget, Label, Button, Bar, Meter, Led, Switch, Arc, Qrcode, Line, TextArea, Pixmap,
+        PixButton, Area, Image
+    );
+    if handle.is_null() {
+        return Err(DisplayError::Config(format!(
+            "widget uid:{} type:{} has no lvgl object",
+            widget.get_uid(),
+            get_widget_type(widget)
+        )));
+    }
+    Ok(handle)
+}
+
+// read current value directly from lvgl object
+pub fn get_widget_value(widget: &LvglWidget) -> WidgetValue {
+    let handle = match get_widget_handle(widget) {
+        Ok(handle) => handle,
+        Err(_) => return WidgetValue::None,
+    };
+
+    unsafe {
+        match widget {
+            LvglWidget::Switch(_) => WidgetValue::Bool(cglue::capi_obj_is_checked(handle)),
+            LvglWidget::Led(_) => WidgetValue::Bool(cglue::capi_led_get_brightness(handle) > 0),
+            LvglWidget::Bar(_) => WidgetValue::Int(cglue::capi_bar_get_value(handle)),
+            LvglWidget::Arc(_) => WidgetValue::Int(cglue::capi_arc_get_value(handle)),
+            LvglWidget::Meter(_) => WidgetValue::Int(cglue::capi_meter_get_value(handle)),
+            LvglWidget::Label(_) => cstr_to_value(cglue::capi_label_get_text(handle)),
+            LvglWidget::TextArea(_) => cstr_to_value(cglue::capi_textarea_get_text(handle)),
+            LvglWidget::Button(_) => cstr_to_value(cglue::capi_button_get_text(handle)),
+            _ => WidgetValue::None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct WidgetGeometry {
+    pub x: i16,
+    pub y: i16,
+    pub width: i16,
+    pub height: i16,
+}
+
+pub fn get_widget_geometry(widget: &LvglWidget) -> WidgetGeometry {
+    let mut geometry = WidgetGeometry::default();
+    if let Ok(handle) = get_widget_handle(widget) {
+        unsafe {
+            cglue::capi_obj_get_coords(
+                handle,
+                &mut geometry.x,
+                &mut geometry.y,
+                &mut geometry.width,
+                &mut geometry.height,
+            )
+        };
+    }
+    geometry
+}
+
+// what is effectively displayed on screen for a given widget
+#[derive(Serialize, Debug, Clone)]
+pub struct WidgetState {
+    pub uid: &'static str,
+    pub widget: &'static str,
+    pub value: WidgetValue,
+    pub hidden: bool,
+    pub disabled: bool,
+    #[serde(flatten)]
+    pub geometry: WidgetGeometry,
+}
+
+impl WidgetState {
+    pub fn new(widget: &LvglWidget) -> Self {
+        let (hidden, disabled) = match get_widget_handle(widget) {
+            Ok(handle) => unsafe {
+                (
+                    cglue::capi_obj_is_hidden(handle),
+                    cglue::capi_obj_is_disabled(handle),
+                )
+            },
+            Err(_) => (false, false),
+        };
+
+        WidgetState {
+            uid: widget.get_uid(),
+            widget: get_widget_type(widget),
+            value: get_widget_value(widget),
+            hidden,
+            disabled,
+            geometry: get_widget_geometry(widget),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+// kind of value accepted by widget verb
+pub fn get_widget_input(widget: &LvglWidget) -> &'static str {
+    match widget {
+        LvglWidget::Label(_)
+        | LvglWidget::Button(_)
+        | LvglWidget::TextArea(_)
+        | LvglWidget::Qrcode(_) => "text",
+        LvglWidget::Bar(_) | LvglWidget::Arc(_) | LvglWidget::Meter(_) => "i32",
+        LvglWidget::Led(_) | LvglWidget::Switch(_) => "onoff",
+        LvglWidget::PixButton(_) | LvglWidget::Pixmap(_) => "pixmap",
+        _ => "none",
+    }
+}
+
+// widget capabilities as exposed to remote client for panel introspection
+#[derive(Serialize, Debug, Clone)]
+pub struct WidgetInfo {
+    pub uid: &'static str,
+    pub widget: &'static str,
+    pub info: &'static str,
+    pub action: &'static str,
+    pub input: &'static str,
+    #[serde(flatten)]
+    pub geometry: WidgetGeometry,
+}
+
+impl WidgetInfo {
+    pub fn new(widget: &LvglWidget) -> Self {
+        WidgetInfo {
+            uid: widget.get_uid(),
+            widget: get_widget_type(widget),
+            info: widget.get_info(),
+            action: widget.get_action(),
+            input: get_widget_input(widget),
+            geometry: get_widget_geometry(widget),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+// json event payload pushed to clients on widget activity
+#[derive(Serialize, Debug, Clone)]
+pub struct WidgetEvent {
+    pub uid: &'static str,
+    pub widget: &'static str,
+    pub event: &'static str,
+    pub timestamp: u64,
+    pub value: WidgetValue,
+}
+
+impl WidgetEvent {
+    pub fn new(widget: &LvglWidget, uid: &'static str, event: &LvglEvent) -> Self {
+        let timestamp = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(duration) => duration.as_millis() as u64,
+            Err(_) => 0,
+        };
+
+        WidgetEvent {
+            uid,
+            widget: get_widget_type(widget),
+            event: get_event_name(event),
+            timestamp,
+            value: get_widget_value(widget),
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        // WidgetEvent only holds string/number, serialization cannot fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
@@ -0,0 +1,85 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// run with: cargo test -p lvgl_gui --test asset-pipeline
+
+#[path = "../capi/build-assets.rs"]
+mod assets;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+const WIDTH: u32 = 3;
+const HEIGHT: u32 = 2;
+
+fn write_png(path: &PathBuf) {
+    let rgba: Vec<u8> = (0..WIDTH * HEIGHT)
+        .flat_map(|index| [0x10, 0x20, 0x30, (index * 0x20) as u8])
+        .collect();
+    let file = fs::File::create(path).unwrap();
+    let mut encoder = png::Encoder::new(file, WIDTH, HEIGHT);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&rgba).unwrap();
+}
+
+// bytes within LV_COLOR_DEPTH == 32 map of generated image
+fn depth32_map(source: &str) -> Vec<u8> {
+    let block = source
+        .split("#if LV_COLOR_DEPTH == 32\n")
+        .nth(1)
+        .and_then(|block| block.split("#endif").next())
+        .expect("depth 32 map");
+    block
+        .split(',')
+        .filter_map(|byte| byte.trim().strip_prefix("0x"))
+        .map(|byte| u8::from_str_radix(byte, 16).unwrap())
+        .collect()
+}
+
+// env is process wide, both color formats are generated from the same test
+#[test]
+fn depth32_map_matches_data_size() {
+    let root = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("asset-pipeline");
+    let (source_dir, out_dir) = (root.join("png"), root.join("out"));
+    fs::create_dir_all(&source_dir).unwrap();
+    fs::create_dir_all(&out_dir).unwrap();
+    write_png(&source_dir.join("pipeline-test.png"));
+    env::set_var("LVGL_ASSETS_DIR", &source_dir);
+    let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    let count = (WIDTH * HEIGHT) as usize;
+
+    env::set_var("LVGL_ASSET_CF", "true_color_alpha");
+    assets::generate(&manifest_dir, &out_dir);
+    let source = fs::read_to_string(out_dir.join("img-generated.c")).unwrap();
+    assert!(source.contains(&format!(
+        ".data_size = {} * LV_IMG_PX_SIZE_ALPHA_BYTE,",
+        count
+    )));
+    let map = depth32_map(&source);
+    assert_eq!(map.len(), count * 4);
+    for (index, pixel) in map.chunks(4).enumerate() {
+        assert_eq!(pixel, [0x30, 0x20, 0x10, (index * 0x20) as u8]);
+    }
+
+    env::set_var("LVGL_ASSET_CF", "true_color");
+    assets::generate(&manifest_dir, &out_dir);
+    let source = fs::read_to_string(out_dir.join("img-generated.c")).unwrap();
+    assert!(source.contains(&format!(".data_size = {} * LV_COLOR_SIZE / 8,", count)));
+    let map = depth32_map(&source);
+    assert_eq!(map.len(), count * 4);
+    for pixel in map.chunks(4) {
+        assert_eq!(pixel, [0x30, 0x20, 0x10, 0xFF]);
+    }
+}
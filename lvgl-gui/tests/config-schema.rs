@@ -0,0 +1,139 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// run with: cargo test -p lvgl_gui --test config-schema
+
+use lvgl_gui::prelude::*;
+
+fn errors(json: &str) -> Vec<String> {
+    match binding_config_from_json(json) {
+        Ok(_) => Vec::new(),
+        Err(errors) => errors.iter().map(|error| error.to_string()).collect(),
+    }
+}
+
+// every widget type with every key, screens are checked through the same panel entries
+const EXAMPLE_PANEL: &str = r##"[
+    {"type":"label","uid":"Label-1","info":"label","x":10,"y":10,"width":200,"height":40,"font":"std_22",
+        "value":"hello","disable":false,"color":"#000000","background":"white",
+        "border":{"width":2,"color":{"red":0,"green":255,"blue":0}},"title":{"text":"title","x":4,"y":2,"font":"std_10"}},
+    {"type":"button","uid":"Button-1","font":"std_14","value":"push"},
+    {"type":"bar","uid":"Bar-1","min":-10,"max":120,"value":50,
+        "gradient":{"vertical":true,"start":"red","end":"#0000FF"}},
+    {"type":"meter","uid":"Meter-1","needle_width":4,"needle_length":80,"needle_color":"grey","value":20,
+        "tic":{"width":2,"length":10,"count":41,"major_length":15,"major_nth":8,"color":"grey","major_color":"black"},
+        "zones":[{"start":80,"end":100,"width":3,"color":"red"}]},
+    {"type":"led","uid":"Led-1","brightness":255,"value":true},
+    {"type":"switch","uid":"Switch-1","value":false},
+    {"type":"arc","uid":"Arc-1","start":0,"end":270,"value":40},
+    {"type":"qrcode","uid":"Qrcode-1","dark":"black","light":"white","size":120,"value":"https://github.com/tux-evse"},
+    {"type":"line","uid":"Line-1","line_width":3,"rounded":true,"points":[{"x":0,"y":0},{"x":100,"y":50}]},
+    {"type":"textarea","uid":"Text-1","value":"text"},
+    {"type":"pixmap","uid":"Pixmap-1","icon":"WIFI","states":{"on":"WIFI","off":"CLOSE"}},
+    {"type":"pixbutton","uid":"Pixbutton-1","value":"off","states":{"on":"nfc_on","off":"nfc_off"}},
+    {"type":"area","uid":"Area-1","widgets":[{"type":"label","uid":"Label-2","value":"nested"}]}
+]"##;
+
+fn example_configs() -> Vec<(String, String)> {
+    let mut configs = Vec::new();
+    for name in [
+        "binding-native-lvgl.json",
+        "binding-portal.json",
+        "binding-target.json",
+    ] {
+        let path = format!("{}/../afb-binding/etc/{}", env!("CARGO_MANIFEST_DIR"), name);
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).expect(&path)).expect(&path);
+        configs.push((name.to_string(), json["binding"][0].to_string()));
+    }
+    let display = r#""display":{"x_res":1024,"y_res":600}"#;
+    configs.push((
+        "example panel".to_string(),
+        format!(r#"{{{},"panel":{}}}"#, display, EXAMPLE_PANEL),
+    ));
+    configs.push((
+        "example screens".to_string(),
+        format!(
+            r#"{{{},"screens":[{{"uid":"main","background":"blue_grey","panel":{}}}]}}"#,
+            display, EXAMPLE_PANEL
+        ),
+    ));
+    configs
+}
+
+#[test]
+fn shipped_configs_are_valid() {
+    for (name, config) in example_configs() {
+        assert_eq!(errors(config.as_str()), Vec::<String>::new(), "{}", name);
+    }
+}
+
+// schema and panel structures should accept the same configs (cf: config-schema.rs header)
+#[test]
+fn schema_matches_panel_config() {
+    for (name, config) in example_configs() {
+        let config = binding_config_from_json(config.as_str()).expect(name.as_str());
+        if let Some(screens) = config.get("screens") {
+            if let Err(error) = screens_from_json(screens.to_string().as_str()) {
+                panic!("{}: {}", name, error);
+            }
+        }
+        if let Some(panel) = config.get("panel") {
+            if let Err(error) = panel_from_json(panel.to_string().as_str()) {
+                panic!("{}: {}", name, error);
+            }
+        }
+    }
+}
+
+#[test]
+fn defaults_are_applied() {
+    let config = binding_config_from_json(r#"{"display":{"x_res":1024,"y_res":600}}"#)
+        .expect("valid config");
+    assert_eq!(config["display"]["ratio"], 1);
+    assert_eq!(config["theme"], "day");
+    assert_eq!(config["snapshot_dir"], "/tmp");
+}
+
+#[test]
+fn errors_name_path_and_suggest_fix() {
+    let errors = errors(
+        r#"{"display":{"x_res":1024,"y_res":600,"ration":1},
+            "panel":[{"type":"lable","uid":"Label-1"},{"type":"bar","uid":"Bar-1","vlaue":10,"border":{"width":2}}]}"#,
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "config 'display.ration' unknown key 'ration', did you mean 'ratio'?",
+            "config 'panel[0].type' unknown widget type 'lable', did you mean 'label'?",
+            "config 'panel[1].vlaue' unknown key 'vlaue', did you mean 'value'?",
+            "config 'panel[1].border.color' missing mandatory key",
+        ]
+    );
+}
+
+#[test]
+fn errors_are_case_sensitive_and_bounded() {
+    let errors = errors(
+        r#"{"display":{"x_res":1024,"y_res":600,"driver":"Offscreen"},
+            "panel":[{"type":"Label","uid":"Label-1"},{"type":"bar","uid":"Bar-1","x":40000,"y":-1.5}]}"#,
+    );
+    assert_eq!(
+        errors,
+        vec![
+            "config 'display.driver' unknown value 'Offscreen', did you mean 'offscreen'?",
+            "config 'panel[0].type' unknown widget type 'Label', did you mean 'label'?",
+            "config 'panel[1].x' 40000 out of range [-32768, 32767]",
+            "config 'panel[1].y' expect integer, got number",
+        ]
+    );
+}
@@ -0,0 +1,67 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// run with: cargo test -p lvgl_gui --test evdev-calibration
+
+use lvgl_gui::prelude::*;
+
+fn point(raw: [f32; 2], screen: [f32; 2]) -> CalibrationPoint {
+    CalibrationPoint { raw, screen }
+}
+
+#[test]
+fn points_solve_swapped_and_inverted_panel() {
+    // panel x axis goes down the screen, y axis goes right to left
+    let points = [
+        point([0.1, 0.1], [920.7, 59.9]),
+        point([0.9, 0.1], [920.7, 539.1]),
+        point([0.1, 0.9], [102.3, 59.9]),
+    ];
+    let calibration = calibration_from_points(&points, 1024, 600).unwrap();
+    let matrix = calibration.matrix;
+    for point in &points {
+        let (x, y) = (point.raw[0], point.raw[1]);
+        let screen_x = (matrix[0] * x + matrix[1] * y + matrix[2]) * 1023.0;
+        let screen_y = (matrix[3] * x + matrix[4] * y + matrix[5]) * 599.0;
+        assert!((screen_x - point.screen[0]).abs() < 0.5);
+        assert!((screen_y - point.screen[1]).abs() < 0.5);
+    }
+    assert!(!calibration.swap_xy && !calibration.invert_x && !calibration.invert_y);
+}
+
+#[test]
+fn points_are_rejected_when_aligned_or_missing() {
+    let aligned = [
+        point([0.1, 0.1], [100.0, 60.0]),
+        point([0.5, 0.5], [500.0, 300.0]),
+        point([0.9, 0.9], [900.0, 540.0]),
+    ];
+    assert!(calibration_from_points(&aligned, 1024, 600).is_err());
+    assert!(calibration_from_points(&aligned[..2], 1024, 600).is_err());
+}
+
+#[test]
+fn evdev_config_defaults() {
+    let configs = evdev_from_json(
+        r#"[{"device":"/dev/input/event1","calibration":{"swap_xy":true}},
+            {"device":"/dev/input/event2","type":"keyboard"}]"#,
+    )
+    .unwrap();
+    assert_eq!(configs[0].kind, EvdevKind::Touch);
+    assert!(configs[0].calibration.swap_xy);
+    assert_eq!(
+        configs[0].calibration.matrix,
+        [1.0, 0.0, 0.0, 0.0, 1.0, 0.0]
+    );
+    assert_eq!(configs[1].kind, EvdevKind::Keyboard);
+    assert!(evdev_from_json(r#"[{"device":"/dev/input/event1","type":"mouse"}]"#).is_err());
+}
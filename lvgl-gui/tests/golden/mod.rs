@@ -0,0 +1,135 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// Golden image harness: compare offscreen frames with reference PNGs from tests/golden/reference.
+// LVGL_GOLDEN_UPDATE=1 (re)writes references instead of comparing them.
+
+use lvgl_gui::prelude::*;
+use std::fs::{self, File};
+use std::path::PathBuf;
+
+fn reference_path(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden/reference")
+        .join(format!("{}.png", name))
+}
+
+fn diff_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden-diff")
+}
+
+fn read_png(path: &PathBuf) -> Result<DisplayFrame, String> {
+    let file = File::open(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND);
+    let mut reader = decoder
+        .read_info()
+        .map_err(|error| format!("{}: {}", path.display(), error))?;
+    let mut data = vec![0u8; reader.output_buffer_size()];
+    let info = reader
+        .next_frame(&mut data)
+        .map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    // normalize reference to RGBA8888
+    let rgba = match info.color_type {
+        png::ColorType::Rgba => data[..info.buffer_size()].to_vec(),
+        png::ColorType::Rgb => data[..info.buffer_size()]
+            .chunks(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 0xFF])
+            .collect(),
+        color => return Err(format!("{}: unsupported color type {:?}", path.display(), color)),
+    };
+
+    Ok(DisplayFrame {
+        width: info.width,
+        height: info.height,
+        rgba,
+    })
+}
+
+fn write_png(path: &PathBuf, frame: &DisplayFrame) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|error| format!("{}: {}", parent.display(), error))?;
+    }
+    fs::write(path, frame.to_png()?).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+// mismatching pixels are painted red over a dimmed copy of the reference
+fn diff_frame(reference: &DisplayFrame, frame: &DisplayFrame, tolerance: u8) -> (usize, DisplayFrame) {
+    let mut count = 0;
+    let mut rgba = Vec::with_capacity(frame.rgba.len());
+
+    for (expected, actual) in reference.rgba.chunks(4).zip(frame.rgba.chunks(4)) {
+        let mismatch = expected
+            .iter()
+            .zip(actual.iter())
+            .take(3)
+            .any(|(expected, actual)| expected.abs_diff(*actual) > tolerance);
+        if mismatch {
+            count += 1;
+            rgba.extend_from_slice(&[0xFF, 0x00, 0x00, 0xFF]);
+        } else {
+            rgba.extend_from_slice(&[expected[0] / 4, expected[1] / 4, expected[2] / 4, 0xFF]);
+        }
+    }
+
+    let diff = DisplayFrame {
+        width: frame.width,
+        height: frame.height,
+        rgba,
+    };
+    (count, diff)
+}
+
+// compare frame with named reference, each color channel may differ from 'tolerance'
+pub fn check_frame(name: &str, frame: &DisplayFrame, tolerance: u8) -> Result<(), String> {
+    let reference_path = reference_path(name);
+    if std::env::var("LVGL_GOLDEN_UPDATE").is_ok() {
+        return write_png(&reference_path, frame);
+    }
+    if !reference_path.exists() {
+        return Err(format!(
+            "missing reference {} (generate it with LVGL_GOLDEN_UPDATE=1)",
+            reference_path.display()
+        ));
+    }
+
+    let reference = read_png(&reference_path)?;
+    let actual_path = diff_dir().join(format!("{}-actual.png", name));
+    if reference.width != frame.width || reference.height != frame.height {
+        write_png(&actual_path, frame)?;
+        return Err(format!(
+            "{}: size {}x{} does not match reference {}x{} (actual: {})",
+            name,
+            frame.width,
+            frame.height,
+            reference.width,
+            reference.height,
+            actual_path.display()
+        ));
+    }
+
+    let (count, diff) = diff_frame(&reference, frame, tolerance);
+    if count > 0 {
+        let diff_path = diff_dir().join(format!("{}-diff.png", name));
+        write_png(&actual_path, frame)?;
+        write_png(&diff_path, &diff)?;
+        return Err(format!(
+            "{}: {} pixel(s) differ from reference (actual: {} diff: {})",
+            name,
+            count,
+            actual_path.display(),
+            diff_path.display()
+        ));
+    }
+    Ok(())
+}
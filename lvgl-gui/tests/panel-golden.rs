@@ -0,0 +1,74 @@
+/*
+ * Copyright (C) 2015-2022 IoT.bzh Company
+ * Author: Fulup Ar Foll <fulup@iot.bzh>
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *   http://www.apache.org/licenses/LICENSE-2.0
+ *
+ */
+
+// run with: cargo test -p lvgl_gui --features offscreen
+#![cfg(feature = "offscreen")]
+
+mod golden;
+
+use lvgl_gui::prelude::*;
+
+// per color channel tolerance, absorbs antialiasing differences between lvgl builds
+const TOLERANCE: u8 = 8;
+
+fn render(display: &DisplayHandle, name: &str, failures: &mut Vec<String>) {
+    let frame = display.refresh().get_frame().expect("offscreen frame");
+    if let Err(error) = golden::check_frame(name, &frame, TOLERANCE) {
+        failures.push(error);
+    }
+}
+
+// lvgl is a process singleton, every scenario runs sequentially from one test
+#[test]
+fn demo_panel_golden() {
+    let mut display = DisplayHandle::create_with_driver(1024, 600, 1, DisplayDriver::Offscreen)
+        .expect("offscreen display");
+    display
+        .set_theme(
+            LvglColor::LIGHT_BLUE(),
+            LvglColor::BLUE_GREY(),
+            false,
+            LvglMkFont::std_14(),
+        )
+        .draw_panel()
+        .finalize_panel()
+        .expect("demo panel uids");
+
+    let mut failures = Vec::new();
+    render(&display, "demo-panel", &mut failures);
+
+    let bar = display.get_widget::<LvglBar>("Bar-1").expect("Bar-1");
+    bar.set_value(85);
+    match display.get_widget::<LvglLed>("Bar-1") {
+        Err(DisplayError::WrongType {
+            expected, found, ..
+        }) => assert_eq!((expected, found), ("led", "bar")),
+        _ => panic!("Bar-1 typed as led should fail"),
+    }
+    let meter = display.get_widget::<LvglMeter>("Meter").expect("Meter");
+    meter.set_value(10);
+    let widget = display.find_widget("Meter").expect("Meter");
+    assert_eq!(get_widget_value(widget), WidgetValue::Int(10));
+    render(&display, "demo-bar-meter", &mut failures);
+
+    let switch = display.get_widget::<LvglSwitch>("Switch-1").expect("Switch-1");
+    switch.set_value(true);
+    let led = display.get_widget::<LvglLed>("Led-Red").expect("Led-Red");
+    led.set_on(false);
+    render(&display, "demo-switch-led", &mut failures);
+
+    let text = display.get_widget::<LvglTextArea>("Text-Area").expect("Text-Area");
+    text.set_value("golden image regression");
+    render(&display, "demo-text-area", &mut failures);
+
+    assert!(failures.is_empty(), "golden mismatch:\n{}", failures.join("\n"));
+}